@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+use std::fs::{read, read_dir};
+use std::io::{self, Write};
+use std::path::Path;
+
+const P3D_LE: &[u8] = b"P3D\xff";
+const P3D_BE: &[u8] = b"\xffD3P";
+/// read as text, tokens in them are checked one by one
+const SCRIPT_EXTS: &[&str] = &["txt", "lua", "script", "xml", "ini", "cfg", "con", "mfk", "csv", "json"];
+/// what the artists saved as, the game only ever loads the p3d made from it
+const SOURCE_EXTS: &[&str] = &["tga", "bmp", "psd", "max", "ma", "mb"];
+
+/// backslashes to slashes, lowercase, no drive or ./ in front, source art
+/// comes back as written and with .p3d, since either may be what was hashed
+pub fn normalize(s: &str) -> Vec<String> {
+    let mut s = s.trim().trim_matches(|c| c == '"' || c == '\'').replace('\\', "/").to_lowercase();
+    // escaped backslashes in scripts
+    while s.contains("//") { s = s.replace("//", "/"); }
+    let s = match s.as_bytes() {
+        [d, b':', b'/', ..] if d.is_ascii_alphabetic() => s[3..].to_string(),
+        _ => s,
+    };
+    let s = s.trim_start_matches("./").trim_start_matches('/').to_string();
+    match s.rsplit_once('.') {
+        Some((stem, ext)) if SOURCE_EXTS.contains(&ext) => vec![format!("{}.p3d", stem), s.clone()],
+        _ => vec![s],
+    }
+}
+
+/// has a separator or ends in a short extension, and only has characters
+/// a file name would, no spaces since the names in a dict line can't have them
+fn path_like(s: &str) -> bool {
+    if s.len() < 3 || s.len() > 260 || s.starts_with('.') { return false; }
+    if !s.chars().all(|c| c.is_ascii_alphanumeric() || "_-./\\:".contains(c)) { return false; }
+    if !s.chars().any(|c| c.is_ascii_alphabetic()) { return false; }
+    let has_ext = match s.rsplit_once('.') {
+        Some((stem, ext)) => !stem.is_empty() && (1..=4).contains(&ext.len())
+            && ext.starts_with(|c: char| c.is_ascii_alphabetic()) && ext.chars().all(|c| c.is_ascii_alphanumeric()),
+        None => false,
+    };
+    has_ext || s.contains('/') || s.contains('\\')
+}
+
+/// printable runs of at least 4 bytes, like `strings`
+fn strings(data: &[u8], v: &mut Vec<String>) {
+    for run in data.split(|b| !(0x20..0x7f).contains(b)) {
+        if run.len() >= 4 {
+            v.push(String::from_utf8_lossy(run).to_string());
+        }
+    }
+}
+
+/// the length prefixed name most chunks start with, at the start of the
+/// data for Root, textures and shaders and after a u32 for X20
+fn p3d_names(input: &[u8], big_endian: bool, v: &mut Vec<String>) {
+    let u32_at = |b: &[u8], i: usize| -> Option<usize> {
+        let b: [u8; 4] = b.get(i..i + 4)?.try_into().ok()?;
+        Some(if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) } as usize)
+    };
+    let pstring = |data: &[u8], i: usize| -> Option<String> {
+        let len = *data.get(i)? as usize;
+        let s = data.get(i + 1..i + 1 + len)?;
+        let s = String::from_utf8_lossy(s).trim_end_matches('\0').to_string();
+        (s.len() >= 2 && s.chars().all(|c| c.is_ascii_graphic() || c == ' ')).then_some(s)
+    };
+    let mut input = input;
+    while let (Some(ds), Some(cs)) = (u32_at(input, 4), u32_at(input, 8)) {
+        if ds < 12 || cs < ds || cs > input.len() { return; }
+        let data = &input[12..ds];
+        v.extend(pstring(data, 0).or_else(|| pstring(data, 4)));
+        p3d_names(&input[ds..cs], big_endian, v);
+        input = &input[cs..];
+    }
+}
+
+/// candidate names from one file, p3ds by their chunk names, scripts by
+/// their tokens and anything else by its strings
+/// progress goes to `log`, stderr when the candidates are on stdout
+pub fn harvest_file(path: &Path, v: &mut BTreeSet<String>, log: &mut dyn Write) -> io::Result<()> {
+    let data = read(path)?;
+    let before = v.len();
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let mut found: Vec<String> = vec![];
+    if data.starts_with(P3D_LE) || data.starts_with(P3D_BE) {
+        // chunk names are asset names, with or without a path or extension
+        let mut names = vec![];
+        p3d_names(&data, data.starts_with(P3D_BE), &mut names);
+        for name in names.iter() {
+            normalize(name).iter().for_each(|n| insert(n, v));
+        }
+        strings(&data, &mut found);
+    } else if SCRIPT_EXTS.contains(&ext.as_str()) {
+        let text = String::from_utf8_lossy(&data);
+        found.extend(text.split(|c: char| c.is_whitespace() || "\"'()[]{},;=<>".contains(c)).map(str::to_string));
+    } else {
+        strings(&data, &mut found);
+    }
+    // strings runs can hold a path and more, so split those on spaces too
+    for s in found.iter().flat_map(|s| std::iter::once(s.as_str()).chain(s.split(' '))) {
+        if path_like(s) {
+            normalize(s).iter().for_each(|n| insert(n, v));
+        }
+    }
+    writeln!(log, " GOOD {:?} {} candidates", path, v.len() - before)?;
+    Ok(())
+}
+
+/// the path and, since the dev machine's folders are often in front of
+/// the part the game uses, everything after each slash
+fn insert(path: &str, v: &mut BTreeSet<String>) {
+    if path.is_empty() || path.contains(char::is_whitespace) { return; }
+    let mut rest = path;
+    v.insert(rest.to_string());
+    while let Some((_, after)) = rest.split_once('/') {
+        if !after.is_empty() { v.insert(after.to_string()); }
+        rest = after;
+    }
+}
+
+pub fn harvest(path: &Path, v: &mut BTreeSet<String>, log: &mut dyn Write) -> io::Result<()> {
+    if path.is_dir() {
+        for entry in read_dir(path)? {
+            harvest(&entry?.path(), v, log)?;
+        }
+        Ok(())
+    } else {
+        harvest_file(path, v, log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_keeps_source_ext_and_p3d() {
+        assert_eq!(normalize("C:\\Art\\Crash\\Fur.TGA"), vec!["art/crash/fur.p3d", "art/crash/fur.tga"]);
+        assert_eq!(normalize("./levels/l1_e1.p3d"), vec!["levels/l1_e1.p3d"]);
+        assert_eq!(normalize("\"scripts//boss.lua\""), vec!["scripts/boss.lua"]);
+    }
+
+    #[test]
+    fn path_like_needs_ext_or_separator() {
+        assert!(path_like("art/crash"));
+        assert!(path_like("fur.tga"));
+        assert!(!path_like("fur"));
+        assert!(!path_like(".tga"));
+        assert!(!path_like("1234.56"));
+        assert!(!path_like("art/crash fur.tga"));
+    }
+
+    #[test]
+    fn insert_adds_every_tail() {
+        let mut v = BTreeSet::new();
+        insert("dev/art/crash/fur.p3d", &mut v);
+        insert("has space/fur.p3d", &mut v);
+        assert_eq!(v.into_iter().collect::<Vec<_>>(),
+            vec!["art/crash/fur.p3d", "crash/fur.p3d", "dev/art/crash/fur.p3d", "fur.p3d"]);
+    }
+
+    #[test]
+    fn harvest_script_and_strings() {
+        let dir = std::env::temp_dir().join(format!("hashdict_harvest_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.lua"), "load(\"levels\\l1.p3d\") -- fur.tga").unwrap();
+        std::fs::write(dir.join("b.bin"), b"\0\0art/x.p3d and more\0\x01").unwrap();
+        let (mut v, mut log) = (BTreeSet::new(), vec![]);
+        harvest(&dir, &mut v, &mut log).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(v.into_iter().collect::<Vec<_>>(),
+            vec!["art/x.p3d", "fur.p3d", "fur.tga", "l1.p3d", "levels/l1.p3d", "x.p3d"]);
+        assert_eq!(String::from_utf8(log).unwrap().lines().count(), 2);
+    }
+}
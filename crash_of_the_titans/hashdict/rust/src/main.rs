@@ -5,6 +5,11 @@ use std::ops::{Index, IndexMut};
 use std::io::{self, prelude::*, Read, Write};
 use std::path::{Path, PathBuf};
 
+mod harvest;
+mod rename;
+mod resolve;
+use resolve::{cott_hash, guessed, resolve, unresolved};
+
 fn get_hashes<'a>(path: String) -> Option<collections::HashMap<String, String>> {
     let mut hashes = collections::HashMap::new();
 
@@ -25,46 +30,89 @@ fn get_hashes<'a>(path: String) -> Option<collections::HashMap<String, String>>
 }
 
 
-fn visit_dirs(dir: &Path, og: &Path, hash_map: &collections::HashMap<String, String>) -> io::Result<()> {
-    if dir.is_dir() {
-        for entry in read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                visit_dirs(&path, og, hash_map)?;
-            } else {
-                let lets = swap_hash_filename(hash_map, path.to_path_buf());
-                if let Some(p) = lets {
-                    let new = og.join(p);
-                    _ = create_dir_all(new.with_file_name(""));
-                    _ = rename(path, new);
-                }
-            }
-        }
-    }
-    Ok(())
+/// takes `--name value` out of the args
+fn flag(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == name)?;
+    let value = args.get(i + 1).cloned();
+    args.drain(i..(i + 2).min(args.len()));
+    value
 }
 
-fn swap_hash_filename(
-    hash_map: &collections::HashMap<String, String>,
-    path: PathBuf,
-) -> Option<PathBuf> {
-    if let Some(stem) = path.file_stem().unwrap().to_str() {
-        println!(" {:?}", stem);
-        if hash_map.contains_key(stem) {
-            println!(" ^^^^^^^^^^^^^^ ");
-            return Some(PathBuf::from(&hash_map[stem]));
-        };
-    };
-    None
+fn usage() -> io::Result<()> {
+    println!("hashdict <hashed_dir> <dict.txt> [--dry-run] [--journal <journal.txt>] [--unchecked-hash]");
+    println!("hashdict undo <journal.txt>");
+    println!("hashdict hash <path>..   (crc-32 like rcf's, a guess at CotT's hash that no real pair has confirmed)");
+    println!("hashdict harvest <file|dir>.. [--out <candidates.txt>]");
+    println!("hashdict resolve <hashed_dir> <dict.txt> <wordlist|pattern>.. [--out <new.txt>]");
+    Ok(())
 }
 
 fn main() -> io::Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
+    let out = flag(&mut args, "--out");
+    let journal = flag(&mut args, "--journal");
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let unchecked_hash = args.iter().any(|a| a == "--unchecked-hash");
+    args.retain(|a| a != "--dry-run" && a != "--unchecked-hash");
 
-    let hashes = get_hashes(args[2].clone()).unwrap();
-    let path: &Path = &Path::new(&args[1]);
-    _ = visit_dirs(path, path, &hashes);
+    match args.as_slice() {
+        [_, mode, paths @ ..] if mode == "hash" => {
+            for path in paths {
+                println!("{} {:08x}", path, cott_hash(path));
+            }
+        }
+        // exes, p3ds and scripts, "path hash" lines get_hashes can read
+        [_, mode, paths @ ..] if mode == "harvest" && !paths.is_empty() => {
+            let mut names = collections::BTreeSet::new();
+            // progress on stderr when stdout is the list
+            let mut log: Box<dyn Write> = if out.is_some() { Box::new(io::stdout()) } else { Box::new(io::stderr()) };
+            for path in paths {
+                harvest::harvest(Path::new(path), &mut names, &mut log)?;
+            }
+            let lines: String = names.iter().map(|n| format!("{} {:08x}\n", n, cott_hash(n))).collect();
+            match out {
+                Some(out) => OpenOptions::new().create(true).write(true).truncate(true).open(out)?.write_all(lines.as_bytes())?,
+                None => print!("{}", lines),
+            }
+            writeln!(log, "{} candidates", names.len())?;
+        }
+        // patterns take {a,b}, {1-9} and {level}
+        [_, mode, dir, dict, sources @ ..] if mode == "resolve" && !sources.is_empty() => {
+            let hashes = get_hashes(dict.clone()).unwrap();
+            let mut hashed = collections::BTreeMap::new();
+            unresolved(Path::new(dir), &mut hashed)?;
+            let lines = resolve(&hashes, &hashed, sources)?;
+            if let Some(out) = out {
+                OpenOptions::new().create(true).write(true).truncate(true).open(out)?
+                    .write_all(lines.iter().map(|l| format!("{}\n", l)).collect::<String>().as_bytes())?;
+            }
+        }
+        [_, mode, journal] if mode == "undo" => rename::undo(Path::new(journal))?,
+        // nothing's moved onto a path that's taken or that two hashes want
+        [_, dir, dict] => {
+            let hashes = get_hashes(dict.clone()).unwrap();
+            let path: &Path = Path::new(dir);
+            let plan = rename::plan(path, &hashes)?;
+            plan.print();
+            // a harvest or resolve's lines only say what the guessed hash
+            // makes of a name, nothing's moved on them until it's confirmed
+            let guessed = guessed(&hashes);
+            if !guessed.is_empty() && !unchecked_hash && !dry_run {
+                for (hash, path) in guessed.iter().take(5) {
+                    println!(" UNCHECKED {} {}", path, hash);
+                }
+                println!(" BAD {} dict lines only cott_hash could have made and no real pair has confirmed it, nothing moved", guessed.len());
+                println!("add one to resolve::CONFIRMED, drop those lines, or run with --unchecked-hash");
+                return Ok(());
+            }
+            if !dry_run {
+                let journal = journal.map(PathBuf::from).unwrap_or_else(|| path.join("hashdict_journal.txt"));
+                plan.execute(&journal)?;
+                println!("undo with: hashdict undo {:?}", journal);
+            }
+        }
+        _ => return usage(),
+    }
 
     Ok(())
 }
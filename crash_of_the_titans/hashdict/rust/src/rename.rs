@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{create_dir_all, read_dir, read_to_string, rename, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::resolve::parse_hash;
+
+/// what a run would do, worked out before anything's touched
+#[derive(Debug, Default)]
+pub struct Plan {
+    /// (hashed file, where it goes)
+    pub moves: Vec<(PathBuf, PathBuf)>,
+    /// a destination more than one hash file wants, none of them move
+    pub collisions: BTreeMap<PathBuf, Vec<PathBuf>>,
+    /// a destination that's already there
+    pub exists: Vec<(PathBuf, PathBuf)>,
+    /// hash named files the dictionary has nothing for
+    pub unmatched: Vec<PathBuf>,
+}
+
+/// the dictionary's path for a file named by its hash
+pub fn swap_hash_filename(hash_map: &HashMap<String, String>, path: &Path) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_str()?;
+    hash_map.get(stem).map(PathBuf::from)
+}
+
+fn visit_dirs(dir: &Path, og: &Path, hash_map: &HashMap<String, String>, v: &mut Vec<(PathBuf, PathBuf)>, unmatched: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            visit_dirs(&path, og, hash_map, v, unmatched)?;
+        } else if let Some(p) = swap_hash_filename(hash_map, &path) {
+            v.push((path, og.join(p)));
+        } else if path.file_stem().and_then(|s| s.to_str()).and_then(parse_hash).is_some() {
+            unmatched.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub fn plan(dir: &Path, hash_map: &HashMap<String, String>) -> io::Result<Plan> {
+    let (mut wanted, mut unmatched) = (vec![], vec![]);
+    visit_dirs(dir, dir, hash_map, &mut wanted, &mut unmatched)?;
+
+    let mut by_to: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for (from, to) in wanted {
+        by_to.entry(to).or_default().push(from);
+    }
+    let mut plan = Plan {unmatched, ..Default::default()};
+    for (to, mut froms) in by_to {
+        if froms.len() > 1 {
+            froms.sort();
+            plan.collisions.insert(to, froms);
+        } else if to.exists() {
+            plan.exists.push((froms.remove(0), to));
+        } else {
+            plan.moves.push((froms.remove(0), to));
+        }
+    }
+    plan.unmatched.sort();
+    Ok(plan)
+}
+
+impl Plan {
+    pub fn print(&self) {
+        for (from, to) in self.moves.iter() {
+            println!(" MOVE {:?} -> {:?}", from, to);
+        }
+        for (to, froms) in self.collisions.iter() {
+            println!(" COLLISION {:?} <- {:?}", to, froms);
+        }
+        for (from, to) in self.exists.iter() {
+            println!(" EXISTS {:?} -> {:?}", from, to);
+        }
+        for path in self.unmatched.iter() {
+            println!(" UNMATCHED {:?}", path);
+        }
+        println!("{} to move, {} colliding on {} paths, {} already there, {} unmatched",
+            self.moves.len(), self.collisions.values().map(Vec::len).sum::<usize>(),
+            self.collisions.len(), self.exists.len(), self.unmatched.len());
+    }
+
+    /// does the moves, each one's written to the journal as it's done so
+    /// an interrupted run can still be undone
+    pub fn execute(&self, journal: &Path) -> io::Result<()> {
+        let mut journal = OpenOptions::new().create(true).append(true).open(journal)?;
+        let (mut done, mut failed) = (0, 0);
+        for (from, to) in self.moves.iter() {
+            // another move in this run may have made it since the plan
+            if to.exists() {
+                println!(" EXISTS {:?}", to);
+                failed += 1;
+                continue;
+            }
+            let moved = match to.parent() {
+                Some(parent) => create_dir_all(parent).and_then(|_| rename(from, to)),
+                None => rename(from, to),
+            };
+            match moved {
+                Ok(()) => {
+                    writeln!(journal, "{}\t{}", from.display(), to.display())?;
+                    done += 1;
+                }
+                Err(e) => {
+                    println!(" BAD {:?} -> {:?} {}", from, to, e);
+                    failed += 1;
+                }
+            }
+        }
+        journal.flush()?;
+        println!("{} moved, {} failed", done, failed);
+        Ok(())
+    }
+}
+
+/// plays a journal backwards, moving everything back to its hash name
+pub fn undo(journal: &Path) -> io::Result<()> {
+    let text = read_to_string(journal)?;
+    let (mut done, mut failed) = (0, 0);
+    for line in text.lines().rev() {
+        let Some((from, to)) = line.split_once('\t') else { continue };
+        let (from, to) = (Path::new(from), Path::new(to));
+        if from.exists() || !to.exists() {
+            println!(" BAD {:?} -> {:?} can't go back", to, from);
+            failed += 1;
+            continue;
+        }
+        match rename(to, from) {
+            Ok(()) => done += 1,
+            Err(e) => {
+                println!(" BAD {:?} -> {:?} {}", to, from, e);
+                failed += 1;
+            }
+        }
+    }
+    println!("{} moved back, {} failed", done, failed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{read, remove_dir_all, write};
+
+    /// hashed files for a move, a collision, a file that's already there,
+    /// one the dict doesn't have and one that isn't a hash at all
+    fn hashed_dir(name: &str) -> (PathBuf, HashMap<String, String>) {
+        let root = std::env::temp_dir().join(format!("hashdict_{}_{}", name, std::process::id()));
+        _ = remove_dir_all(&root);
+        create_dir_all(root.join("sub")).unwrap();
+        for file in ["1a2b3c4d.p3d", "0badf00d", "sub/0badf00e", "5ca1ab1e.txt", "there.txt", "deadbeef.bin", "readme.txt"] {
+            write(root.join(file), file).unwrap();
+        }
+        let dict = [
+            ("1a2b3c4d", "art/crash/fur.p3d"),
+            ("0badf00d", "dup.p3d"),
+            ("0badf00e", "dup.p3d"),
+            ("5ca1ab1e", "there.txt"),
+        ].into_iter().map(|(h, p)| (h.to_string(), p.to_string())).collect();
+        (root, dict)
+    }
+
+    #[test]
+    fn plan_sorts_out_every_file() {
+        let (root, dict) = hashed_dir("plan");
+        let plan = plan(&root, &dict).unwrap();
+        assert_eq!(plan.moves, vec![(root.join("1a2b3c4d.p3d"), root.join("art/crash/fur.p3d"))]);
+        assert_eq!(plan.collisions.into_iter().collect::<Vec<_>>(),
+            vec![(root.join("dup.p3d"), vec![root.join("0badf00d"), root.join("sub/0badf00e")])]);
+        assert_eq!(plan.exists, vec![(root.join("5ca1ab1e.txt"), root.join("there.txt"))]);
+        assert_eq!(plan.unmatched, vec![root.join("deadbeef.bin")]);
+        // nothing's touched until it's executed
+        assert!(root.join("1a2b3c4d.p3d").exists());
+        _ = remove_dir_all(&root);
+    }
+
+    #[test]
+    fn execute_then_undo() {
+        let (root, dict) = hashed_dir("undo");
+        let journal = root.with_extension("journal");
+        _ = std::fs::remove_file(&journal);
+        plan(&root, &dict).unwrap().execute(&journal).unwrap();
+        assert!(!root.join("1a2b3c4d.p3d").exists());
+        assert_eq!(read(root.join("art/crash/fur.p3d")).unwrap(), b"1a2b3c4d.p3d");
+        // collisions and what's already there stay put
+        assert!(root.join("0badf00d").exists() && root.join("sub/0badf00e").exists());
+        assert!(!root.join("dup.p3d").exists());
+        assert_eq!(read(root.join("there.txt")).unwrap(), b"there.txt");
+        assert_eq!(read_to_string(&journal).unwrap().lines().count(), 1);
+
+        undo(&journal).unwrap();
+        assert_eq!(read(root.join("1a2b3c4d.p3d")).unwrap(), b"1a2b3c4d.p3d");
+        assert!(!root.join("art/crash/fur.p3d").exists());
+        // a second undo can't move anything back again
+        undo(&journal).unwrap();
+        assert!(root.join("1a2b3c4d.p3d").exists());
+        _ = remove_dir_all(&root);
+        _ = std::fs::remove_file(&journal);
+    }
+}
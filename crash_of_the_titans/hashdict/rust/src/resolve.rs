@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{read_dir, read_to_string};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// CotT names are taken to hash the same way the RCF starts table does,
+/// crc-32 over the lowercased path with backslashes
+///
+/// a guess, there's no real name and hash pair to check it against yet, so
+/// a resolve that finds nothing may mean the hash is wrong, not the names
+pub fn cott_hash(path: &str) -> u32 {
+    rcf::rcf_hash(path)
+}
+
+/// (path, hash) pairs from CotT itself, `cott_hash` is tested against every
+/// one, empty until there's a real archive or dict line to take one from
+pub const CONFIRMED: &[(&str, u32)] = &[];
+
+/// dict lines only `cott_hash` could have made, a harvest or resolve's,
+/// which can't be trusted to move files until `CONFIRMED` has a pair
+pub fn guessed(dict: &HashMap<String, String>) -> Vec<(&String, &String)> {
+    if !CONFIRMED.is_empty() { return vec![]; }
+    let mut v: Vec<_> = dict.iter().filter(|(hash, path)| parse_hash(hash) == Some(cott_hash(path))).collect();
+    v.sort();
+    v
+}
+
+/// a hash as the dictionary and the extracted file names spell it, 8 hex
+/// digits with or without 0x, any case
+pub fn parse_hash(s: &str) -> Option<u32> {
+    let s = s.trim_start_matches("0x");
+    if s.is_empty() || s.len() > 8 { return None; }
+    u32::from_str_radix(s, 16).ok()
+}
+
+/// every file under `dir` whose stem is a hash, by hash
+pub fn unresolved(dir: &Path, v: &mut BTreeMap<u32, Vec<PathBuf>>) -> io::Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            unresolved(&path, v)?;
+        } else if let Some(hash) = path.file_stem().and_then(|s| s.to_str()).and_then(parse_hash) {
+            v.entry(hash).or_default().push(path);
+        }
+    }
+    Ok(())
+}
+
+/// `{a,b}` alternatives and `{1-9}` ranges, expanded left to right,
+/// `{level}` is every level code we know of
+pub fn expand(pattern: &str, levels: &BTreeSet<String>, v: &mut Vec<String>) {
+    let Some(open) = pattern.find('{') else {
+        v.push(pattern.to_string());
+        return;
+    };
+    let Some(close) = pattern[open..].find('}').map(|c| open + c) else {
+        v.push(pattern.to_string());
+        return;
+    };
+    let (head, body, tail) = (&pattern[..open], &pattern[open + 1..close], &pattern[close + 1..]);
+    let options: Vec<String> = if body == "level" {
+        levels.iter().cloned().collect()
+    } else if let Some((a, b)) = body.split_once('-').and_then(|(a, b)| Some((a.parse::<u32>().ok()?, b.parse::<u32>().ok()?))) {
+        (a..=b).map(|n| n.to_string()).collect()
+    } else {
+        body.split(',').map(str::to_string).collect()
+    };
+    for option in options {
+        expand(&format!("{}{}{}", head, option, tail), levels, v);
+    }
+}
+
+/// L1_E1 or L1_E1_R1 starting at `i`, how long it is
+fn level_code_at(s: &[u8], i: usize) -> Option<usize> {
+    let digits = |mut j: usize| {
+        let start = j;
+        while j < s.len() && s[j].is_ascii_digit() { j += 1; }
+        (j > start).then_some(j)
+    };
+    let letter = |j: usize, c: u8| (s.get(j)?.to_ascii_uppercase() == c).then_some(j + 1);
+    let j = digits(letter(i, b'L')?)?;
+    let j = digits(letter(letter(j, b'_')?, b'E')?)?;
+    let end = letter(j, b'_').and_then(|k| letter(k, b'R')).and_then(digits).unwrap_or(j);
+    Some(end - i)
+}
+
+/// every level code in `s`, as (start, end)
+fn level_codes(s: &str) -> Vec<(usize, usize)> {
+    let bytes = s.as_bytes();
+    let mut v = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match level_code_at(bytes, i) {
+            Some(len) if (i == 0 || !bytes[i - 1].is_ascii_alphanumeric()) => {
+                v.push((i, i + len));
+                i += len;
+            }
+            _ => i += 1,
+        }
+    }
+    v
+}
+
+/// a candidate as is, with each other level code in place of the ones it
+/// has unless it came from a pattern, in each known directory, and with
+/// each known extension if it has none
+fn variants(name: &str, swap_levels: bool, levels: &BTreeSet<String>, dirs: &BTreeSet<String>, exts: &BTreeSet<String>, v: &mut Vec<String>) {
+    let name = name.trim().replace('\\', "/");
+    if name.is_empty() { return; }
+    let mut names = vec![name.clone()];
+    for (start, end) in level_codes(&name).into_iter().filter(|_| swap_levels) {
+        names.extend(levels.iter().map(|l| format!("{}{}{}", &name[..start], l, &name[end..])));
+    }
+    let mut with_ext = vec![];
+    for n in names {
+        let file = n.rsplit('/').next().unwrap_or(&n);
+        if !file.contains('.') {
+            with_ext.extend(exts.iter().map(|e| format!("{}.{}", n, e)));
+        }
+        with_ext.push(n);
+    }
+    for n in with_ext {
+        if !n.contains('/') {
+            v.extend(dirs.iter().map(|d| format!("{}/{}", d, n)));
+        }
+        v.push(n);
+    }
+}
+
+/// the dictionary's names, what hashes are left and the wordlists or
+/// patterns to try, prints and returns the new "path hash" lines
+pub fn resolve(
+    known: &HashMap<String, String>,
+    hashed: &BTreeMap<u32, Vec<PathBuf>>,
+    sources: &[String],
+) -> io::Result<Vec<String>> {
+    // directories, extensions and level codes from the names we already have
+    let (mut dirs, mut exts, mut levels) = (BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+    for path in known.values() {
+        let path = path.replace('\\', "/");
+        if let Some((dir, _)) = path.rsplit_once('/') { dirs.insert(dir.to_string()); }
+        if let Some((_, ext)) = path.rsplit_once('.') { exts.insert(ext.to_lowercase()); }
+        levels.extend(level_codes(&path).into_iter().map(|(s, e)| path[s..e].to_string()));
+    }
+    for paths in hashed.values() {
+        exts.extend(paths.iter().filter_map(|p| p.extension()).map(|e| e.to_string_lossy().to_lowercase()));
+    }
+    // and every code in a range wider than the game's, in case a level has
+    // nothing named yet
+    for l in 1..=9 {
+        for e in 1..=9 {
+            levels.insert(format!("L{}_E{}", l, e));
+            levels.extend((1..=20).map(|r| format!("L{}_E{}_R{}", l, e, r)));
+        }
+    }
+    let known_hashes: BTreeSet<u32> = known.keys().filter_map(|h| parse_hash(h)).collect();
+
+    let mut found: BTreeMap<u32, String> = BTreeMap::new();
+    let mut tried = 0usize;
+    for source in sources {
+        // a file is a wordlist, anything else a pattern
+        let words: Vec<String> = match Path::new(source).is_file() {
+            true => read_to_string(source)?.lines().map(str::to_string).collect(),
+            false => vec![source.clone()],
+        };
+        for word in words {
+            let mut expanded = vec![];
+            expand(word.trim(), &levels, &mut expanded);
+            let mut candidates = vec![];
+            let swap_levels = !word.contains('{');
+            for e in expanded {
+                variants(&e, swap_levels, &levels, &dirs, &exts, &mut candidates);
+            }
+            for candidate in candidates {
+                tried += 1;
+                let hash = cott_hash(&candidate);
+                if hashed.contains_key(&hash) && !known_hashes.contains(&hash) && !found.contains_key(&hash) {
+                    println!(" GOOD {:08x} {}", hash, candidate);
+                    found.insert(hash, candidate);
+                }
+            }
+        }
+    }
+
+    println!("{} candidates tried, {} of {} unresolved hashes found", tried, found.len(),
+        hashed.keys().filter(|h| !known_hashes.contains(h)).count());
+    Ok(found.into_iter().map(|(hash, path)| format!("{} {:08x}", path, hash)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels() -> BTreeSet<String> {
+        ["L1_E1", "L2_E3"].iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn cott_hash_is_rcfs() {
+        // pinned to the crc-32 until there's a real pair to test against
+        assert_eq!(cott_hash("123456789"), 0xcbf4_3926);
+        assert_eq!(cott_hash("art/L1_E1.p3d"), cott_hash("ART\\l1_e1.P3D"));
+    }
+
+    #[test]
+    fn confirmed_pairs() {
+        for &(path, hash) in CONFIRMED {
+            assert_eq!(cott_hash(path), hash, "{}", path);
+        }
+    }
+
+    #[test]
+    fn guessed_lines() {
+        let dict: HashMap<String, String> = [
+            (format!("{:08x}", cott_hash("art/crash.p3d")), "art/crash.p3d".to_string()),
+            ("0badf00d".to_string(), "art/aku.p3d".to_string()),
+        ].into_iter().collect();
+        let guessed = guessed(&dict);
+        assert_eq!(guessed.len(), CONFIRMED.is_empty() as usize);
+        assert!(guessed.iter().all(|(_, path)| *path == "art/crash.p3d"));
+    }
+
+    #[test]
+    fn hashes_as_spelled() {
+        assert_eq!(parse_hash("0x0000abcd"), Some(0xabcd));
+        assert_eq!(parse_hash("DEADBEEF"), Some(0xdead_beef));
+        assert_eq!(parse_hash("123456789"), None);
+        assert_eq!(parse_hash("0x"), None);
+        assert_eq!(parse_hash("wall"), None);
+    }
+
+    #[test]
+    fn expand_patterns() {
+        let mut v = vec![];
+        expand("{a,b}_{1-2}.p3d", &levels(), &mut v);
+        assert_eq!(v, ["a_1.p3d", "a_2.p3d", "b_1.p3d", "b_2.p3d"]);
+        v.clear();
+        expand("art/{level}_R{1-1}.p3d", &levels(), &mut v);
+        assert_eq!(v, ["art/L1_E1_R1.p3d", "art/L2_E3_R1.p3d"]);
+        v.clear();
+        expand("open{brace", &levels(), &mut v);
+        assert_eq!(v, ["open{brace"]);
+    }
+
+    #[test]
+    fn level_codes_in_names() {
+        assert_eq!(level_codes("art/L1_E1_R2_ext.p3d"), vec![(4, 12)]);
+        assert_eq!(level_codes("l3_e2/l3_e2.rcf"), vec![(0, 5), (6, 11)]);
+        // not inside a word, and not without the E
+        assert_eq!(level_codes("xl1_e1 L1_R1"), vec![]);
+    }
+
+    #[test]
+    fn variants_swap_levels_and_add_dirs_and_exts() {
+        let dirs: BTreeSet<String> = ["art".to_string()].into();
+        let exts: BTreeSet<String> = ["p3d".to_string()].into();
+        let mut v = vec![];
+        variants("L1_E1", true, &levels(), &dirs, &exts, &mut v);
+        for name in ["L1_E1", "L2_E3.p3d", "art/L2_E3.p3d", "art/L1_E1"] {
+            assert!(v.contains(&name.to_string()), "{} not in {:?}", name, v);
+        }
+        v.clear();
+        variants("L1_E1.p3d", false, &levels(), &dirs, &exts, &mut v);
+        assert_eq!(v, ["art/L1_E1.p3d", "L1_E1.p3d"]);
+    }
+}
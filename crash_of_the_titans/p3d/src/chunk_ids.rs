@@ -1,40 +1,121 @@
+// ids as the tree prints them, the header's first 4 bytes read big endian
+// offsets and sizes in the comments are from L1_E1_R1_ext.p3d
 
+pub const ROOT         : u32 = 0x00_00_01_00; //       0xa8e4d
+pub const X03_00_01_00 : u32 = 0x03_00_01_00; // leaf  0xa8d6c  0x24  bbox, min and max
+pub const X04_00_01_00 : u32 = 0x04_00_01_00; // leaf  0xa8d90  0x1c  bsphere, centre and radius
+pub const X05_00_01_00 : u32 = 0x05_00_01_00; // leaf                 vb
+pub const X06_00_01_00 : u32 = 0x06_00_01_00; // leaf                 normals, num, 3 floats each, pure3d's 0x10006
+pub const X07_00_01_00 : u32 = 0x07_00_01_00; // leaf                 uvs, num, channel, 2 floats each, pure3d's 0x10007
+pub const X08_00_01_00 : u32 = 0x08_00_01_00; // leaf                 rgba table
+pub const X0A_00_01_00 : u32 = 0x0a_00_01_00; // leaf                 index buffer
+pub const X0B_00_01_00 : u32 = 0x0b_00_01_00; // leaf                 matrix list, skin prim groups only
+pub const X0C_00_01_00 : u32 = 0x0c_00_01_00; // leaf                 weights
+pub const X0D_00_01_00 : u32 = 0x0d_00_01_00; // leaf                 matrix palette
+pub const X10_00_01_00 : u32 = 0x10_00_01_00; // leaf                 a byte per vertex, packed normals?
+pub const X11_00_01_00 : u32 = 0x11_00_01_00; // leaf                 vertex shader? pure3d's 0x10011
+pub const X17_00_01_00 : u32 = 0x17_00_01_00; // leaf  0xa8dd4  0x10
+pub const X1D_00_01_00 : u32 = 0x1d_00_01_00; //       0xa8dfd  0x10
+pub const X20_00_01_00 : u32 = 0x20_00_01_00; //                      mesh
+pub const X21_00_01_00 : u32 = 0x21_00_01_00; // leaf  0xa8e75  0x2c
+pub const X26_00_01_00 : u32 = 0x26_00_01_00; //
+pub const X27_00_01_00 : u32 = 0x27_00_01_00; //
 
-const todo!        : u32 = 0x00_00_01_00; //       0xa8e4d  
-const todo!        : u32 = 0x03_00_01_00; // leaf  0xa8d6c  0x24  6 floats
-const todo!        : u32 = 0x04_00_01_00; // leaf  0xa8d90  0x1c
-const vb           : u32 = 0x05_00_01_00; // leaf  
-const normals      : u32 = 0x07_00_01_00; // leaf  
-const colors       : u32 = 0x08_00_01_00; // leaf                 rgba table
-const ib           : u32 = 0x0a_00_01_00; // leaf                 index buffer
-const ib           : u32 = 0x11_00_01_00; // leaf               
-const todo!        : u32 = 0x17_00_01_00; // leaf  0xa8dd4  0x10
-const todo!        : u32 = 0x1d_00_01_00; //       0xa8dfd  0x10
-const todo!        : u32 = 0x20_00_01_00; // leaf
-const todo!        : u32 = 0x21_00_01_00; // leaf  0xa8e75  0x2c  
-const todo!        : u32 = 0x26_00_01_00; // 
-const todo!        : u32 = 0x27_00_01_00; // 
+// characters, laid out like SHAR's, skeleton 0x4500, skin 0x10001 and
+// animation 0x121000 byte swapped
+pub const SKIN                  : u32 = 0x01_00_01_00;
+pub const SKELETON              : u32 = 0x00_45_00_00;
+pub const SKELETON_JOINT        : u32 = 0x01_45_00_00;
+pub const ANIMATION             : u32 = 0x00_10_12_00;
+pub const ANIMATION_GROUP       : u32 = 0x01_10_12_00;
+pub const ANIMATION_GROUP_LIST  : u32 = 0x02_10_12_00;
+pub const ANIMATION_SIZE        : u32 = 0x04_10_12_00;
+pub const VECTOR_1DOF_CHANNEL   : u32 = 0x02_11_12_00;
+pub const VECTOR_2DOF_CHANNEL   : u32 = 0x03_11_12_00;
+pub const VECTOR_3DOF_CHANNEL   : u32 = 0x04_11_12_00;
+pub const QUATERNION_CHANNEL    : u32 = 0x05_11_12_00;
+pub const COMPRESSED_QUATERNION_CHANNEL : u32 = 0x11_11_12_00;
 
-//
-const todo!: u32     = 0x00_20_12_00; // leaf  0xa8dac  0x14
-const todo!: u32     = 0x01_20_12_00; // leaf  0xa8dc0  0x14
+pub const X00_20_12_00 : u32 = 0x00_20_12_00; // leaf  0xa8dac  0x14
+pub const X01_20_12_00 : u32 = 0x01_20_12_00; // leaf  0xa8dc0  0x14
 
+pub const TGA_COL      : u32 = 0x00_10_01_00; //                      shader, SHAR's 0x00011000
+pub const X02_10_01_00 : u32 = 0x02_10_01_00; // leaf                 texture param
+pub const X03_10_01_00 : u32 = 0x03_10_01_00; // leaf                 int param
+pub const X04_10_01_00 : u32 = 0x04_10_01_00; // leaf                 float param
+pub const X05_10_01_00 : u32 = 0x05_10_01_00; // leaf                 colour param
+pub const SHADER         : u32 = TGA_COL;
+pub const SHADER_TEXTURE : u32 = X02_10_01_00;
+pub const SHADER_INT     : u32 = X03_10_01_00;
+pub const SHADER_FLOAT   : u32 = X04_10_01_00;
+pub const SHADER_COLOR   : u32 = X05_10_01_00;
 
+pub const TGA_1        : u32 = 0x00_90_01_00;
+pub const TGA_2        : u32 = 0x01_90_01_00;
+pub const TGA_3        : u32 = 0x02_90_01_00; //                      png
 
-const TGA_col: u32 = 0x00_10_01_00; // 
-const todo!: u32   = 0x02_10_01_00; // 
+pub const SATURATED_DATA     : u32 = 0x00_60_01_07;
+pub const X01_00_01_07       : u32 = 0x01_00_01_07;
+pub const X0A_00_01_07       : u32 = 0x0a_00_01_07; // big
+pub const SURFACE_MAT_DATA   : u32 = 0x12_60_01_07;
+pub const INT_COLOR_LEFT_KEY : u32 = 0x15_60_01_07;
+pub const ROOM_COLLISION     : u32 = 0x25_00_01_07; // big
+pub const X06_00_00_07       : u32 = 0x06_00_00_07; // room_collision verts?
+pub const X03_00_00_07       : u32 = 0x03_00_00_07; // room_collision triangles?
 
-const TGA_1: u32 = 0x00_90_01_00;
+// placement, SHAR's locator 0x03000005 and trigger volume 0x03000006 and
+// pure3d's locator 0x00014000, byte swapped
+pub const LOCATOR        : u32 = 0x05_00_00_03;
+pub const TRIGGER_VOLUME : u32 = 0x06_00_00_03;
+pub const P3D_LOCATOR    : u32 = 0x00_40_01_00;
 
+pub const P3D: u32 = 0x50_33_44_FF;
 
-const SaturatedData     :u32 = 0x00_60_01_07;
-const todo!             :u32 = 0x01_00_01_07;
-const todo!             :u32 = 0x0a_00_01_07; // big
-const SurfaceMatData    :u32 = 0x12_60_01_07;
-const intColor_left_key :u32 = 0x15_60_01_07;
-const room_collision    :u32 = 0x25_00_01_07; // big
-
-
-
-const P3D: u32 = 0x50_33_44_FF;
+/// every id we have a name for, the rest only get printed as hex
+pub const CHUNK_IDS: &[(u32, &str)] = &[
+    (P3D,                "P3D"),
+    (ROOT,               "root"),
+    (X03_00_01_00,       "bbox"),
+    (X04_00_01_00,       "bsphere"),
+    (X05_00_01_00,       "vb"),
+    (X06_00_01_00,       "normals"),
+    (X07_00_01_00,       "uvs"),
+    (X08_00_01_00,       "colors"),
+    (X0A_00_01_00,       "ib"),
+    (X0B_00_01_00,       "matrix_list"),
+    (X0C_00_01_00,       "weights"),
+    (X0D_00_01_00,       "matrix_palette"),
+    (X11_00_01_00,       "vertex_shader"),
+    (X20_00_01_00,       "mesh"),
+    (SKIN,               "skin"),
+    (SKELETON,           "skeleton"),
+    (SKELETON_JOINT,     "joint"),
+    (ANIMATION,          "animation"),
+    (ANIMATION_GROUP,    "animation_group"),
+    (ANIMATION_GROUP_LIST, "animation_group_list"),
+    (ANIMATION_SIZE,     "animation_size"),
+    (VECTOR_1DOF_CHANNEL, "vector_1dof_channel"),
+    (VECTOR_2DOF_CHANNEL, "vector_2dof_channel"),
+    (VECTOR_3DOF_CHANNEL, "vector_3dof_channel"),
+    (QUATERNION_CHANNEL, "quaternion_channel"),
+    (COMPRESSED_QUATERNION_CHANNEL, "compressed_quaternion_channel"),
+    (SHADER,             "shader"),
+    (SHADER_TEXTURE,     "texture_param"),
+    (SHADER_INT,         "int_param"),
+    (SHADER_FLOAT,       "float_param"),
+    (SHADER_COLOR,       "colour_param"),
+    (TGA_1,              "TGA_1"),
+    (TGA_2,              "TGA_2"),
+    (TGA_3,              "TGA_3"),
+    (SATURATED_DATA,     "SaturatedData"),
+    (SURFACE_MAT_DATA,   "SurfaceMatData"),
+    (INT_COLOR_LEFT_KEY, "intColor_left_key"),
+    (ROOM_COLLISION,     "room_collision"),
+    (LOCATOR,            "locator"),
+    (TRIGGER_VOLUME,     "trigger_volume"),
+    (P3D_LOCATOR,        "p3d_locator"),
+];
 
+pub fn name(id: u32) -> Option<&'static str> {
+    CHUNK_IDS.iter().find(|(i, _)| *i == id).map(|(_, name)| *name)
+}
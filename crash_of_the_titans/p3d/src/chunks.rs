@@ -5,39 +5,35 @@ use std::{
 use nom::{
     IResult,
     Parser,
-    error::ParseError,
+    error::{ErrorKind, ParseError},
     bytes::complete::take,
     multi::{count, many0},
-    number::complete::{be_u32, le_u8, le_u32, le_f32},
+    number::{Endianness, complete::{be_u32, le_u8, le_u32, le_f32, u32}},
     sequence::tuple,
 };
 
-use crate::col::{Root, X20, col};//, ColorTable, Normals};
-
-pub const ROOT: u32         = 0x00_00_01_00;
-pub const X03_00_01_00: u32 = 0x03_00_01_00;
-pub const X04_00_01_00: u32 = 0x04_00_01_00;
-pub const X05_00_01_00: u32 = 0x05_00_01_00;
-pub const X06_00_01_00: u32 = 0x06_00_01_00;
-pub const X07_00_01_00: u32 = 0x07_00_01_00;
-pub const X08_00_01_00: u32 = 0x08_00_01_00;
-pub const X0A_00_01_00: u32 = 0x0a_00_01_00;
-// pub const IB: u32           = 0x0a_00_01_00;
-
-pub const X10_00_01_00: u32 = 0x10_00_01_00;
-// pub const X11_00_01_00: u32 = 0x11_00_01_00;
-// pub const X17_00_01_00: u32 = 0x17_00_01_00;
-// pub const X1D_00_01_00: u32 = 0x1d_00_01_00;
-// pub const X20_00_01_00: u32 = 0x20_00_01_00;
-// pub const X21_00_01_00: u32 = 0x21_00_01_00;
-// pub const X26_00_01_00: u32 = 0x26_00_01_00;
-// pub const X27_00_01_00: u32 = 0x27_00_01_00;
-
-// const X00_20_12_00: u32 = 0x00_20_12_00;
-// const X01_20_12_00: u32 = 0x01_20_12_00;
+use crate::col::{Root, X20, col};
+use crate::endian::id_u32;//, ColorTable, Normals};
+
+pub use crate::chunk_ids::{
+    ROOT,
+    X03_00_01_00,
+    X04_00_01_00,
+    X05_00_01_00,
+    X06_00_01_00,
+    X07_00_01_00,
+    X08_00_01_00,
+    X0A_00_01_00,
+    X0B_00_01_00,
+    X0C_00_01_00,
+    X0D_00_01_00,
+    X10_00_01_00,
+    X11_00_01_00,
+};
 
+/// `endian` is whatever `endian::detect` made of the file's magic
 pub trait FromBytes {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Self, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Self, E>
         where
             Self: Sized,
             E: ParseError<&'a [u8]> + Debug;
@@ -46,23 +42,59 @@ pub trait FromBytes {
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Header {pub id: u32, pub ds: u32, pub cs: u32}
 impl Header {
-    pub fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Header, E>
+    pub fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Header, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
         // dbg!(&input[..12]);
         let (input, (id, ds, cs)) =
-            tuple((be_u32::<&'a [u8], E>, le_u32, le_u32))(input)?;//.unwrap();
+            tuple((id_u32::<&'a [u8], E>(endian), u32(endian), u32(endian)))(input)?;//.unwrap();
         Ok((input, Header {id, ds, cs}))
     }
 }
 
-pub fn chunk_wrapper<'a, E, T>(input: &'a [u8]) -> IResult<&'a [u8], T, E>
+/// (id, data, children) for each chunk directly in `input`, stops at the
+/// first header whose sizes don't fit, `rest` is what's left from there
+pub struct Children<'a> {
+    input: &'a [u8],
+    endian: Endianness,
+}
+impl<'a> Children<'a> {
+    pub fn rest(&self) -> &'a [u8] { self.input }
+}
+impl<'a> Iterator for Children<'a> {
+    type Item = (u32, &'a [u8], &'a [u8]);
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, Header {id, ds, cs}) = Header::from_bytes::<()>(self.input, self.endian).ok()?;
+        let (ds, cs) = (ds as usize, cs as usize);
+        if ds < 12 || cs < ds || cs > self.input.len() { return None; }
+        let chunk = (id, &self.input[12..ds], &self.input[ds..cs]);
+        self.input = &self.input[cs..];
+        Some(chunk)
+    }
+}
+pub fn children(input: &[u8], endian: Endianness) -> Children<'_> {
+    Children {input, endian}
+}
+
+pub fn chunk_wrapper<'a, E, T>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], T, E>
     where
         E: ParseError<&'a [u8]> + Debug,
         T: FromBytes, {
-    let (input, header) = Header::from_bytes(input)?;
+    let (input, header) = Header::from_bytes(input, endian)?;
     println!("{:x}", &header.id);
-    T::from_bytes(input)
+    T::from_bytes(input, endian)
+}
+
+/// like `chunk_wrapper` but `T` only gets the chunk's own data, anything
+/// it doesn't read is skipped instead of throwing off the next header
+pub fn sized_chunk<'a, E, T>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], T, E>
+    where
+        E: ParseError<&'a [u8]> + Debug,
+        T: FromBytes, {
+    let (_, header) = Header::from_bytes(input, endian)?;
+    let (input, data) = take(header.ds.max(12))(input)?;
+    let (_, t) = T::from_bytes(&data[12..], endian)?;
+    Ok((input, t))
 }
 
 pub fn skip<'a, E>() -> Box<dyn Parser<&'a [u8], Chunks, E>>
@@ -80,47 +112,65 @@ pub enum Chunks {
     Col(Root, Vec<X20>),    
 }
 impl Chunks {
-    pub fn from_id<'a, E>(id: u32) -> Box<dyn Parser<&'a [u8], Chunks, E>>
+    pub fn from_id<'a, E>(id: u32, endian: Endianness) -> Box<dyn Parser<&'a [u8], Chunks, E>>
         where
             E: ParseError<&'a [u8]> + Debug, {
         match id {
-            0x00_00_01_00 => col(),
+            ROOT          => col(endian),
             _             => skip(),
         }
     }
 }
 
-pub fn chunk_paris<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], ChunkType, E>
+/// a chunk and what's under it, fewer than 12 bytes left is an error that
+/// ends the siblings, anything wrong past the header fails the whole tree
+/// so a bad file is reported instead of half read
+pub fn chunk_paris<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], ChunkType, E>
     where
         E: ParseError<&'a [u8]> + Debug, {
     const HEADER_SIZE: u32 = 12;
     
     // let mut input = input;
-    let (mut input, header_bytes) =
+    let (input, header_bytes) =
         take::<u32, &'a [u8], E>(HEADER_SIZE)(input)?;
-    let (_, header) = Header::from_bytes::<E>(header_bytes).unwrap();
+    let (_, header) = Header::from_bytes::<E>(header_bytes, endian)?;
     println!("id={:x} ds={:x} cs={:x}", header.id.swap_bytes(), header.ds, header.cs);
 
+    chunk_body::<E>(input, &header, endian).map_err(|e| match e {
+        nom::Err::Error(e) => nom::Err::Failure(e),
+        e => e,
+    })
+}
+
+fn chunk_body<'a, E>(input: &'a [u8], header: &Header, endian: Endianness) -> IResult<&'a [u8], ChunkType, E>
+    where
+        E: ParseError<&'a [u8]> + Debug, {
+    const HEADER_SIZE: u32 = 12;
+    if header.ds < HEADER_SIZE || header.cs < header.ds {
+        return Err(nom::Err::Failure(E::from_error_kind(input, ErrorKind::Verify)));
+    }
     let chunkslice_size = header.cs-HEADER_SIZE;
     let dataslice_size = header.ds-HEADER_SIZE;
 
+    let mut input = input;
     let mut data_slice: &[u8] = &[];
     let mut chunk_slice: &[u8] = &[];
 
-    if let Chunks::Skip = Chunks::from_id::<E>(header.id).parse(input).unwrap().1 {
+    // only roots have a parser, and they read their children themselves
+    if header.id != ROOT {
         (input, chunk_slice) =
-            take::<u32, &'a [u8], E>(chunkslice_size)(input).unwrap();
+            take::<u32, &'a [u8], E>(chunkslice_size)(input)?;
         (chunk_slice, data_slice) =
-            take::<u32, &'a [u8], E>(dataslice_size)(chunk_slice).unwrap();
+            take::<u32, &'a [u8], E>(dataslice_size)(chunk_slice)?;
     } else {
         (input, data_slice) =
-            take::<u32, &'a [u8], E>(chunkslice_size)(input).unwrap();
+            take::<u32, &'a [u8], E>(chunkslice_size)(input)?;
     }
 
     let (_remaining_dataslice, chunk) =
-        Chunks::from_id::<E>(header.id).parse(data_slice)?;//.unwrap();
+        Chunks::from_id::<E>(header.id, endian).parse(data_slice)?;
     let (_remaining_chunkslice, sub_chunks) =
-        many0(chunk_paris::<E>)(&chunk_slice).unwrap();
+        many0(|i| chunk_paris::<E>(i, endian))(chunk_slice)?;
     // assert_eq!(_remaining_chunkslice, &[]);
 
     Ok((input, ChunkType {parent: (chunk, sub_chunks)}))
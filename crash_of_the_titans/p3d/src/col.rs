@@ -8,12 +8,12 @@ use nom::{
     IResult, Parser, error::ParseError,
     bytes::complete::take,
     multi::{count, many0},
-    number::complete::{be_u32, le_f32, le_u8, le_u32},
+    number::{Endianness, complete::{be_u32, f32, le_u8, u32}},
     sequence::tuple,
 };
 
 use crate::utils::{Vec3f, RGBA};
-use crate::chunks::{FromBytes, Header, Chunks, chunk_wrapper,
+use crate::chunks::{FromBytes, Header, Chunks, chunk_wrapper, sized_chunk,
     X03_00_01_00,
     X04_00_01_00,
     X05_00_01_00,
@@ -21,65 +21,97 @@ use crate::chunks::{FromBytes, Header, Chunks, chunk_wrapper,
     X07_00_01_00,
     X08_00_01_00,
     X0A_00_01_00,
+    X0B_00_01_00,
+    X0C_00_01_00,
+    X0D_00_01_00,
     X10_00_01_00,
+    X11_00_01_00,
 };
-use crate::weird_chunks::{X03, X04, X06, X10};
+use crate::chunk_ids::{X00_20_12_00, X01_20_12_00, X17_00_01_00, X1D_00_01_00, X21_00_01_00};
+use crate::weird_chunks::{BBox, BSphere, X00_20_12, X01_20_12, X06, X10, X17, X1d, X21};
 
-pub fn col<'a, E>() -> Box<dyn Parser<&'a [u8], Chunks, E>>
+pub fn col<'a, E>(endian: Endianness) -> Box<dyn Parser<&'a [u8], Chunks, E>>
 where E: ParseError<&'a [u8]> + Debug {
     Box::new(move |input: &'a [u8],| {
-        let (_, root_header) = Header::from_bytes::<E>(input).unwrap();
-        let (input, root) = Root::from_bytes::<E>(input).unwrap();
-        dbg!(&root);
-
-        let (_, x21_header) = Header::from_bytes::<E>(input).unwrap();
-        dbg!(&x21_header);
-        let (input, x21) = take::<u32, &'a [u8], E>(x21_header.ds)(input).unwrap();
-
-        let (input, x20s) = count::<
-            &'a [u8], X20, E, fn(&'a [u8]) -> IResult<&'a [u8], X20, E>
-        >(chunk_wrapper::<E, X20>, root.num as usize)(input).unwrap();
-
-        let (_, x00_20_12_header) = Header::from_bytes::<E>(input)?;//.unwrap();
-        let (input, x00_20_12) = take::<u32, &'a [u8], E>(x00_20_12_header.ds)(input)?;//.unwrap();
-        let (_, x01_20_12_header) = Header::from_bytes::<E>(input)?;//.unwrap();
-        let (input, x01_20_12) = take::<u32, &'a [u8], E>(x01_20_12_header.ds)(input)?;//.unwrap();
-        let (_, x17_header) = Header::from_bytes::<E>(input)?;//.unwrap();
-        let (input, x17) = take::<u32, &'a [u8], E>(x17_header.ds)(input)?;//.unwrap();
-        let (_, x1d_header) = Header::from_bytes::<E>(input)?;//.unwrap();
-        let (input, x1d) = take::<u32, &'a [u8], E>(x1d_header.ds)(input)?;//.unwrap();
-        let (_, x1d_header) = Header::from_bytes::<E>(input)?;//.unwrap();
-        let (input, x1d) = take::<u32, &'a [u8], E>(x1d_header.ds)(input)?;//.unwrap();
-
-        let (input, x03) = chunk_wrapper::<E, X03>(input)?;//.unwrap();
-        let (input, x04) = chunk_wrapper::<E, X04>(input)?;//.unwrap();
+        let (input, mut root) = Root::from_bytes::<E>(input, endian)?;
+
+        // 0x21000100 before the meshes
+        let mut input = input;
+        if let Ok((_, Header {id: X21_00_01_00, ..})) = Header::from_bytes::<E>(input, endian) {
+            let (rest, x21) = sized_chunk::<E, X21>(input, endian)?;
+            (input, root.x21) = (rest, Some(x21));
+        }
+
+        let (input, x20s) = count(|i| chunk_wrapper::<E, X20>(i, endian), root.num as usize)(input)?;
+
+        // then 0x00201200, 0x01201200, 0x17000100 and 0x1d000100, and the
+        // root's bbox and bsphere last, anything else is skipped whole
+        let mut input = input;
+        while let Ok((_, header)) = Header::from_bytes::<E>(input, endian) {
+            match header.id {
+                X00_20_12_00 => (input, root.x00_20_12) = sized_chunk::<E, X00_20_12>(input, endian).map(|(i, x)| (i, Some(x)))?,
+                X01_20_12_00 => (input, root.x01_20_12) = sized_chunk::<E, X01_20_12>(input, endian).map(|(i, x)| (i, Some(x)))?,
+                X17_00_01_00 => (input, root.x17) = sized_chunk::<E, X17>(input, endian).map(|(i, x)| (i, Some(x)))?,
+                X1D_00_01_00 => (input, root.x1d) = x1d::<E>(input, endian).map(|(i, x)| (i, Some(x)))?,
+                X03_00_01_00 => (input, root.bbox) = sized_chunk::<E, BBox>(input, endian)?,
+                X04_00_01_00 => {
+                    (input, root.bsphere) = sized_chunk::<E, BSphere>(input, endian)?;
+                    break;
+                }
+                _ => (input, _) = take::<u32, &'a [u8], E>(header.cs.max(12))(input)?,
+            }
+        }
 
         Ok((input, Chunks::Col(root, x20s)))
     })
 }
 
+/// 0x1d000100 and the 0x1d000100s under it
+fn x1d<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X1d, E>
+where E: ParseError<&'a [u8]> + Debug {
+    let (_, header) = Header::from_bytes::<E>(input, endian)?;
+    let (rest, chunk) = take(header.cs.max(header.ds).max(12))(input)?;
+    let (_, mut parent) = sized_chunk::<E, X1d>(chunk, endian)?;
+    let mut kids = &chunk[(header.ds as usize).max(12)..];
+    while let Ok((_, Header {id: X1D_00_01_00, ..})) = Header::from_bytes::<E>(kids, endian) {
+        let (after, kid) = x1d::<E>(kids, endian)?;
+        parent.children.push(kid);
+        kids = after;
+    }
+    Ok((rest, parent))
+}
+
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Root {
     pub name_len: u8,
     pub name: String,
     pub _null: u32,
     pub num: u32,
+    // filled in by `col` from the chunks around its meshes, what the
+    // fields of the ones in weird_chunks.rs mean is in stuff/notes.txt
+    pub x21: Option<X21>,
+    pub x00_20_12: Option<X00_20_12>,
+    pub x01_20_12: Option<X01_20_12>,
+    pub x17: Option<X17>,
+    pub x1d: Option<X1d>,
+    pub bbox: BBox,
+    pub bsphere: BSphere,
 }
 impl Root {
     pub fn new() -> Root { Default::default() }
 }
 impl FromBytes for Root {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Root, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Root, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, name_len) = le_u8::<&'a [u8], E>(input).unwrap();
-        let (input, name_bytes) = take::<u8, &'a [u8], E>(name_len)(input).unwrap();
-        let (input, _null) = le_u32::<&'a [u8], E>(input).unwrap();
-        let (input, num) = le_u32::<&'a [u8], E>(input).unwrap();
+        let (input, name_len) = le_u8::<&'a [u8], E>(input)?;
+        let (input, name_bytes) = take::<u8, &'a [u8], E>(name_len)(input)?;
+        let (input, _null) = u32::<&'a [u8], E>(endian)(input)?;
+        let (input, num) = u32::<&'a [u8], E>(endian)(input)?;
 
         let name = String::from_utf8_lossy(name_bytes).to_string();
 
-        Ok((input, Root {name_len, name, _null, num}))
+        Ok((input, Root {name_len, name, _null, num, ..Default::default()}))
     }
 }
 
@@ -89,52 +121,46 @@ impl Vb {
     pub fn new() -> Vb { Default::default() }
 }
 impl FromBytes for Vb {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vb, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Vb, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, num) = le_u32(input)?;  
-        let (input, vb) = count::<
-            &'a [u8], Vec3f, E,
-            fn(&'a [u8]) -> IResult<&'a [u8], Vec3f, E>
-        >(Vec3f::from_bytes, num as usize)(input)?;
+        let (input, num) = u32(endian)(input)?;  
+        let (input, vb) = count(|i| Vec3f::from_bytes::<E>(i, endian), num as usize)(input)?;
         Ok((input, Vb {inner: vb}))
     }
 }
 
+/// one uv, 0x07000100 was taken for compressed normals but it's laid out
+/// like pure3d's uv list, num, channel, then 2 floats per vertex, see
+/// chunk_ids.rs for what that's based on
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct Norm {a: f32, b: f32}
-impl FromBytes for Norm {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Norm, E>
+pub struct Uv {pub u: f32, pub v: f32}
+impl FromBytes for Uv {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Uv, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, a) = le_f32(input)?;  
-        let (input, b) = le_f32(input)?;  
-        Ok((input, Norm {a, b}))
+        let (input, u) = f32(endian)(input)?;
+        let (input, v) = f32(endian)(input)?;
+        Ok((input, Uv {u, v}))
     }
 }
 
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct Vn {
-    pub null: u32,
-    pub inner: Vec<Norm>,
+pub struct UvSet {
+    pub channel: u32,
+    pub inner: Vec<Uv>,
 }
-impl Vn {
-    pub fn new() -> Vn { Default::default() }
+impl UvSet {
+    pub fn new() -> UvSet { Default::default() }
 }
-impl FromBytes for Vn {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vn, E>
+impl FromBytes for UvSet {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], UvSet, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, num) = le_u32(input)?;  
-        let (input, null) = le_u32(input)?;  
-        let (input, vn) = count::<
-            &'a [u8], Norm, E,
-            fn(&'a [u8]) -> IResult<&'a [u8], Norm, E>
-        >(
-            Norm::from_bytes,
-            num as usize
-        )(input)?;
-        Ok((input, Vn {null, inner: vn}))
+        let (input, num) = u32(endian)(input)?;
+        let (input, channel) = u32(endian)(input)?;
+        let (input, uvs) = count(|i| Uv::from_bytes::<E>(i, endian), num as usize)(input)?;
+        Ok((input, UvSet {channel, inner: uvs}))
     }
 }
 
@@ -152,14 +178,11 @@ impl Vc {
     }
 }
 impl FromBytes for Vc {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vc, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Vc, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, num) = le_u32(input)?;  
-        let (input, vc) = count::<
-            &'a [u8], RGBA<u8>, E,
-            fn(&'a [u8]) -> IResult<&'a [u8], RGBA<u8>, E>
-        >(RGBA::from_bytes, num as usize)(input)?;
+        let (input, num) = u32(endian)(input)?;  
+        let (input, vc) = count(|i| RGBA::from_bytes::<E>(i, endian), num as usize)(input)?;
         Ok((input, Vc {num, inner: vc}))
     }
 }
@@ -170,39 +193,83 @@ impl Ib {
     pub fn new() -> Ib { Default::default() }
 }
 impl FromBytes for Ib {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Ib, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Ib, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, num) = le_u32(input)?;  
-        let (input, ib) = count::<
-            &'a [u8], u32, E,
-            fn(&'a [u8]) -> IResult<&'a [u8], u32, E>
-        >(le_u32, num as usize)(input)?;
+        let (input, num) = u32(endian)(input)?;  
+        let (input, ib) = count(u32::<&'a [u8], E>(endian), num as usize)(input)?;
         Ok((input, Ib {inner: ib}))
     }
 }
 
+/// a vertex's four indices into the matrix palette, packed in a u32 the
+/// first in the high byte like SHAR's
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct MatrixList {pub inner: Vec<[u8; 4]>}
+impl FromBytes for MatrixList {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], MatrixList, E>
+        where
+            E: ParseError<&'a [u8]> + Debug, {
+        let (input, num) = u32(endian)(input)?;
+        let (input, packed) = count(u32::<&'a [u8], E>(endian), num as usize)(input)?;
+        Ok((input, MatrixList {inner: packed.into_iter().map(|m| m.to_be_bytes()).collect()}))
+    }
+}
+
+/// the first three weights for each vertex, the fourth is whatever's left
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Weights {pub inner: Vec<Vec3f>}
+impl FromBytes for Weights {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Weights, E>
+        where
+            E: ParseError<&'a [u8]> + Debug, {
+        let (input, num) = u32(endian)(input)?;
+        let (input, weights) = count(|i| Vec3f::from_bytes::<E>(i, endian), num as usize)(input)?;
+        Ok((input, Weights {inner: weights}))
+    }
+}
+
+/// the skeleton joint each matrix list index stands for
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Palette {pub inner: Vec<u32>}
+impl FromBytes for Palette {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Palette, E>
+        where
+            E: ParseError<&'a [u8]> + Debug, {
+        let (input, num) = u32(endian)(input)?;
+        let (input, joints) = count(u32::<&'a [u8], E>(endian), num as usize)(input)?;
+        Ok((input, Palette {inner: joints}))
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct X20 {
     pub null_1: u32,
     pub name_len: u8,
     pub name: String,
+    /// null in every file seen so far, pure3d's prim group keeps its
+    /// primitive type here but with nothing else in it that's not relied on
     pub null_2: u32, fake_header_id: u32, fake_header_dw: u32, fake_header_cs: u32, null_3: u32, null_4: u32, one: u32, null_5: u32, null_6: u32,
     pub vb: Option<Vb>,
     pub x10: Option<X10>,
     pub x06: Option<X06>,
-    pub vn: Option<Vn>,
+    pub uvs: Vec<UvSet>,
     pub vc: Option<Vc>,
     pub ib: Option<Ib>,
-    pub x03: Option<X03>,
-    pub x04: Option<X04>,
+    pub ib2: Option<Ib>,
+    pub bbox: Option<BBox>,
+    pub bsphere: Option<BSphere>,
+    /// only in a skin's prim groups
+    pub matrices: Option<MatrixList>,
+    pub weights: Option<Weights>,
+    pub palette: Option<Palette>,
 }
 impl FromBytes for X20 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X20, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X20, E>
         where
             E: ParseError<&'a [u8]> + Debug {
-        let (input, (null_1, name_len)) = tuple((le_u32::<&'a [u8], E>, le_u8))(input).unwrap();
-        let (input, name_bytes) = take::<u8, &'a [u8], E>(name_len)(input).unwrap();
+        let (input, (null_1, name_len)) = tuple((u32::<&'a [u8], E>(endian), le_u8))(input)?;
+        let (input, name_bytes) = take::<u8, &'a [u8], E>(name_len)(input)?;
         let name = String::from_utf8_lossy(name_bytes).to_string();
 
         println!("{}", &name);
@@ -211,19 +278,21 @@ impl FromBytes for X20 {
             null_2, fake_header_id, fake_header_dw,
             fake_header_cs, null_3, null_4, one, null_5, null_6
         )) = tuple((
-            le_u32::<&'a [u8], E>, le_u32, le_u32,
-            le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
-        ))(input).unwrap();
+            u32::<&'a [u8], E>(endian), u32(endian), u32(endian),
+            u32(endian), u32(endian), u32(endian), u32(endian), u32(endian), u32(endian),
+        ))(input)?;
 
+        let mut uvs: Vec<UvSet> = vec![];
+        let mut x11_ib: Option<Ib> = None;
+        let (mut matrices, mut weights, mut palette) = (None, None, None);
         let (
             mut vb,
-            mut vn,
             mut vc,
             mut ib,
             mut x10,
             mut x06,
-            mut x03,
-            mut x04,
+            mut bbox,
+            mut bsphere,
             mut x27,
             mut x26,
             mut x11,
@@ -239,52 +308,251 @@ impl FromBytes for X20 {
             mut xx10,
         ) = Default::default();
         loop {
-            let (_, header) = Header::from_bytes::<E>(input)?;
+            // a skin's last prim group runs to the end of the slice
+            let Ok((_, header)) = Header::from_bytes::<E>(input, endian) else { break };
             match header.id {
                 X03_00_01_00 => {
-                    (input, xx03) = chunk_wrapper::<E, X03>(input).unwrap();
-                    x03 = Some(xx03);
+                    (input, xx03) = chunk_wrapper::<E, BBox>(input, endian)?;
+                    bbox = Some(xx03);
                 }
                 X04_00_01_00 => {
-                    (input, xx04) = chunk_wrapper::<E, X04>(input).unwrap();
-                    x04 = Some(xx04);
+                    (input, xx04) = chunk_wrapper::<E, BSphere>(input, endian)?;
+                    bsphere = Some(xx04);
                 }
                 X05_00_01_00 => {
-                    (input, xx05) = chunk_wrapper::<E, Vb>(input).unwrap();
+                    (input, xx05) = chunk_wrapper::<E, Vb>(input, endian)?;
                     vb = Some(xx05);
                 }
                 X06_00_01_00 => {
-                    (input, xx06) = chunk_wrapper::<E, X06>(input).unwrap();
+                    (input, xx06) = chunk_wrapper::<E, X06>(input, endian)?;
                     x06 = Some(xx06);
                 }
                 X07_00_01_00 => {
-                    (input, xx07) = chunk_wrapper::<E, Vn>(input).unwrap();
-                    vn = Some(xx07);
+                    (input, xx07) = chunk_wrapper::<E, UvSet>(input, endian)?;
+                    uvs.push(xx07);
                 }
                 X08_00_01_00 => {
-                    (input, xx08) = chunk_wrapper::<E, Vc>(input).unwrap();
+                    (input, xx08) = chunk_wrapper::<E, Vc>(input, endian)?;
                     vc = Some(xx08);
                 }
                 X0A_00_01_00 => {
-                    (input, xx0a) = chunk_wrapper::<E, Ib>(input).unwrap();
+                    (input, xx0a) = chunk_wrapper::<E, Ib>(input, endian)?;
                     ib = Some(xx0a);
                 }
                 X10_00_01_00 => {
-                    (input, xx10) = chunk_wrapper::<E, X10>(input).unwrap();
+                    (input, xx10) = chunk_wrapper::<E, X10>(input, endian)?;
                     x10 = Some(xx10);
                 }
-                0x26_00_01_00u32 => { (input, x26) = take::<u32, &'a [u8], E>(header.ds)(input).unwrap(); }
-                0x27_00_01_00u32 => { (input, x27) = take::<u32, &'a [u8], E>(header.ds)(input).unwrap(); }
-                0x11_00_01_00u32 => { (input, x11) = take::<u32, &'a [u8], E>(header.ds)(input).unwrap(); }
+                X0B_00_01_00 => { (input, matrices) = chunk_wrapper::<E, MatrixList>(input, endian).map(|(i, m)| (i, Some(m)))?; }
+                X0C_00_01_00 => { (input, weights) = chunk_wrapper::<E, Weights>(input, endian).map(|(i, w)| (i, Some(w)))?; }
+                X0D_00_01_00 => { (input, palette) = chunk_wrapper::<E, Palette>(input, endian).map(|(i, p)| (i, Some(p)))?; }
+                0x26_00_01_00u32 => { (input, x26) = take::<u32, &'a [u8], E>(header.ds)(input)?; }
+                0x27_00_01_00u32 => { (input, x27) = take::<u32, &'a [u8], E>(header.ds)(input)?; }
+                0x11_00_01_00u32 => {
+                    (input, x11) = take::<u32, &'a [u8], E>(header.ds)(input)?;
+                    match Ib::from_bytes::<()>(x11.get(12..).unwrap_or_default(), endian) {
+                        Ok(([], xx11)) => x11_ib = Some(xx11),
+                        _ => println!(" BAD 0x11000100 in {} isn't an index list, {:#x} bytes, skipped", name, x11.len().saturating_sub(12)),
+                    }
+                }
                 // _ => panic!(" unknown col id {}", header.id),
                 _ => break,
             }
         }
 
+        // see X11_00_01_00 in chunk_ids.rs, 0x11000100 is probably the
+        // vertex shader, it's only taken for indices when they're all verts
+        let num = vb.as_ref().map_or(0, |vb: &Vb| vb.inner.len() as u32);
+        let ib2 = x11_ib.filter(|ib| {
+            let fits = !ib.inner.is_empty() && ib.inner.iter().all(|i| *i < num || *i == 0xffff || *i == 0xffff_ffff);
+            if !fits {
+                println!(" BAD 0x11000100 in {} has {} indices that aren't all in its {} verts, skipped", name, ib.inner.len(), num);
+            }
+            fits
+        });
+
         Ok((input, X20 {
             null_1, name_len, name, null_2, fake_header_id, fake_header_dw,
             fake_header_cs, null_3, null_4, one, null_5, null_6,
-            vb, x10, x06, vn, vc, ib, x03, x04,
+            vb, x10, x06, uvs, vc, ib, ib2, bbox, bsphere, matrices, weights, palette,
         }))
     }
 }
+
+/// JOINTS_0 and WEIGHTS_0
+pub type Skinning = (Vec<[u16; 4]>, Vec<[f32; 4]>);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Primitive { List, Strip }
+
+impl X20 {
+    /// a heuristic, where the prim type is kept isn't known, so it's a
+    /// strip when the indices can't be a list or have a restart in them
+    pub fn primitive(&self) -> Primitive {
+        match self.index_buffers().first() {
+            Some((_, ib)) if ib.inner.len() % 3 != 0 => Primitive::Strip,
+            Some((_, ib)) if ib.inner.iter().any(|i| *i == 0xffff || *i == 0xffff_ffff) => Primitive::Strip,
+            _ => Primitive::List,
+        }
+    }
+
+    /// joints and weights per vertex, joints are skeleton indices, None
+    /// unless it's a skin's and they cover every vertex
+    pub fn skinning(&self) -> Option<Skinning> {
+        let (vb, matrices, weights) = (self.vb.as_ref()?, self.matrices.as_ref()?, self.weights.as_ref()?);
+        let n = vb.inner.len();
+        if matrices.inner.len() != n || weights.inner.len() != n { return None; }
+        let joint = |i: u8| match &self.palette {
+            Some(palette) => palette.inner.get(i as usize).copied().unwrap_or(0) as u16,
+            None => i as u16,
+        };
+        let joints = matrices.inner.iter().map(|m| m.map(joint)).collect();
+        let weights = weights.inner.iter().map(|w| [w.x, w.y, w.z, (1. - w.x - w.y - w.z).max(0.)]).collect();
+        Some((joints, weights))
+    }
+
+    /// 0x0a000100's indices and 0x11000100's when they checked out as
+    /// indices, with the id they came from
+    pub fn index_buffers(&self) -> Vec<(u32, &Ib)> {
+        [(X0A_00_01_00, &self.ib), (X11_00_01_00, &self.ib2)].into_iter()
+            .filter_map(|(id, ib)| Some((id, ib.as_ref()?)))
+            .collect()
+    }
+
+    /// a triangle list whatever the primitive, without degenerates or
+    /// indices past the end of the vertex buffer
+    pub fn triangles(&self, ib: &Ib) -> Vec<[u32; 3]> {
+        let Some(vb) = &self.vb else { return vec![] };
+        let tris = match self.primitive() {
+            Primitive::List => ib.inner.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect(),
+            Primitive::Strip => strip_to_list(&ib.inner),
+        };
+        let num = vb.inner.len() as u32;
+        tris.into_iter()
+            .filter(|t| t.iter().all(|i| *i < num) && t[0] != t[1] && t[1] != t[2] && t[0] != t[2])
+            .collect()
+    }
+}
+
+/// every other triangle of a strip is wound backwards, so swap its first
+/// two, 0xffff/0xffffffff restarts the strip
+pub fn strip_to_list(strip: &[u32]) -> Vec<[u32; 3]> {
+    let mut tris = vec![];
+    for run in strip.split(|i| *i == 0xffff || *i == 0xffff_ffff) {
+        for (i, t) in run.windows(3).enumerate() {
+            if i % 2 == 0 {
+                tris.push([t[0], t[1], t[2]]);
+            } else {
+                tris.push([t[1], t[0], t[2]]);
+            }
+        }
+    }
+    tris
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad(ib: Vec<u32>, ib2: Option<Vec<u32>>) -> X20 {
+        X20 {
+            vb: Some(Vb {inner: vec![Vec3f::default(); 4]}),
+            ib: Some(Ib {inner: ib}),
+            ib2: ib2.map(|inner| Ib {inner}),
+            ..Default::default()
+        }
+    }
+
+    /// a little endian chunk, the id as the tree prints it
+    fn chunk(id: u32, data: &[u8], kids: &[u8]) -> Vec<u8> {
+        let ds = 12 + data.len() as u32;
+        [&id.to_be_bytes()[..], &ds.to_le_bytes(), &(ds + kids.len() as u32).to_le_bytes(), data, kids].concat()
+    }
+    fn le(v: &[u32]) -> Vec<u8> { v.iter().flat_map(|u| u.to_le_bytes()).collect() }
+    fn lef(v: &[f32]) -> Vec<u8> { v.iter().flat_map(|f| f.to_le_bytes()).collect() }
+
+    #[test]
+    fn chunks_around_the_meshes() {
+        let input = [
+            &[4][..], b"room", &le(&[0, 0]),
+            &chunk(X21_00_01_00, &le(&[0, 32, 32, 32, 32, 32, 32, 32]), &[]),
+            &chunk(X00_20_12_00, &lef(&[1.5, -2.]), &[]),
+            &chunk(X01_20_12_00, &le(&[3, 4]), &[]),
+            &chunk(0x01121200, &[], &chunk(0x06131200, &[9; 8], &[])),
+            &chunk(X17_00_01_00, &le(&[1]), &[]),
+            &chunk(X1D_00_01_00, &le(&[0]), &chunk(X1D_00_01_00, &le(&[0]), &[])),
+            &chunk(X03_00_01_00, &lef(&[0., 0., 0., 1., 1., 1.]), &[]),
+            &chunk(X04_00_01_00, &lef(&[0.5, 0.5, 0.5, 1.]), &[]),
+        ].concat();
+        let (rest, Chunks::Col(root, x20s)) = col::<()>(Endianness::Little).parse(&input).unwrap() else { panic!() };
+        assert!(rest.is_empty() && x20s.is_empty());
+        assert_eq!(root.name, "room");
+        assert_eq!(root.x21.unwrap().thirty_two_7, 32);
+        assert_eq!(root.x00_20_12, Some(X00_20_12 {f1: 1.5, f2: -2.}));
+        assert_eq!(root.x01_20_12, Some(X01_20_12 {u1: 3, u2: 4}));
+        assert_eq!(root.x17, Some(X17 {one: 1}));
+        assert_eq!(root.x1d, Some(X1d {null: 0, children: vec![X1d {null: 0, children: vec![]}]}));
+        assert_eq!(root.bsphere.radius, 1.);
+    }
+
+    #[test]
+    fn strip_winding() {
+        assert_eq!(strip_to_list(&[0, 1, 2, 3, 4]), vec![[0, 1, 2], [2, 1, 3], [2, 3, 4]]);
+        assert_eq!(strip_to_list(&[0, 1]), Vec::<[u32; 3]>::new());
+    }
+
+    #[test]
+    fn strip_restarts() {
+        assert_eq!(strip_to_list(&[0, 1, 2, 0xffff, 1, 2, 3]), vec![[0, 1, 2], [1, 2, 3]]);
+        assert_eq!(strip_to_list(&[0, 1, 2, 3, 0xffff_ffff, 4, 5, 6]), vec![[0, 1, 2], [2, 1, 3], [4, 5, 6]]);
+    }
+
+    #[test]
+    fn list_drops_degenerates_and_out_of_range() {
+        let x20 = quad(vec![0, 1, 2, 0, 0, 1, 0, 2, 9, 0, 2, 3], None);
+        assert_eq!(x20.primitive(), Primitive::List);
+        assert_eq!(x20.triangles(x20.ib.as_ref().unwrap()), vec![[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn strip_from_the_indices() {
+        let x20 = quad(vec![0, 1, 3, 2], None);
+        assert_eq!(x20.primitive(), Primitive::Strip);
+        assert_eq!(x20.triangles(x20.ib.as_ref().unwrap()), vec![[0, 1, 3], [3, 1, 2]]);
+        assert_eq!(quad(vec![0, 1, 2, 0xffff, 1, 2, 3], None).primitive(), Primitive::Strip);
+        // null_2 isn't taken for the prim type
+        let mut x20 = quad(vec![0, 1, 2], None);
+        x20.null_2 = 1;
+        assert_eq!(x20.primitive(), Primitive::List);
+    }
+
+    fn x20_with(x11: &[u8]) -> X20 {
+        let verts: Vec<f32> = vec![0.; 12];
+        let input = [
+            &le(&[0])[..], &[1], b"m", &le(&[0; 9]),
+            &chunk(X11_00_01_00, x11, &[]),
+            &chunk(X05_00_01_00, &[le(&[4]), lef(&verts)].concat(), &[]),
+            &chunk(X0A_00_01_00, &le(&[3, 0, 1, 2]), &[]),
+        ].concat();
+        X20::from_bytes::<()>(&input, Endianness::Little).unwrap().1
+    }
+
+    #[test]
+    fn x11_only_when_its_indices_fit() {
+        assert_eq!(x20_with(&le(&[3, 0, 2, 3])).ib2, Some(Ib {inner: vec![0, 2, 3]}));
+        assert_eq!(x20_with(&le(&[3, 0, 2, 4])).ib2, None);
+        assert_eq!(x20_with(&le(&[3, 0, 2])).ib2, None);
+        assert_eq!(x20_with(&le(&[0])).ib2, None);
+        assert_eq!(x20_with(b"\x05vs_01").ib2, None);
+        assert_eq!(x20_with(b"\x05vs_01").ib, Some(Ib {inner: vec![0, 1, 2]}));
+    }
+
+    #[test]
+    fn both_index_buffers() {
+        let x20 = quad(vec![0, 1, 2], Some(vec![0, 2, 3]));
+        let buffers = x20.index_buffers();
+        assert_eq!(buffers.iter().map(|b| b.0).collect::<Vec<_>>(), vec![X0A_00_01_00, X11_00_01_00]);
+        assert_eq!(x20.triangles(buffers[1].1), vec![[0, 2, 3]]);
+        assert_eq!(quad(vec![0, 1, 2], None).index_buffers().len(), 1);
+    }
+}
@@ -0,0 +1,58 @@
+use std::ops::RangeFrom;
+use nom::{
+    IResult, InputIter, InputLength, Slice,
+    error::ParseError,
+    number::{Endianness, complete::u32},
+};
+
+// PC files start "P3D\xff", the 360 and PS3 ones byte swap all of it
+pub const MAGIC_LE: &[u8] = b"P3D\xff";
+pub const MAGIC_BE: &[u8] = b"\xffD3P";
+
+/// the byte order from the file's magic, little endian if it has neither,
+/// every parser takes what this returns instead of guessing again
+pub fn detect(input: &[u8]) -> Endianness {
+    if input.starts_with(MAGIC_BE) { Endianness::Big } else { Endianness::Little }
+}
+
+/// ids are kept as the PC files' bytes read big endian, which is the other
+/// way round to everything else in the file whichever it is
+pub fn id_u32<I, E: ParseError<I>>(endian: Endianness) -> impl Fn(I) -> IResult<I, u32, E>
+where I: Slice<RangeFrom<usize>> + InputIter<Item = u8> + InputLength {
+    u32(match endian {
+        Endianness::Big => Endianness::Little,
+        _ => Endianness::Big,
+    })
+}
+
+/// a u32 written back the way `u32(endian)` reads it
+pub fn u32_bytes(v: u32, endian: Endianness) -> [u8; 4] {
+    match endian {
+        Endianness::Big => v.to_be_bytes(),
+        _ => v.to_le_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunks::Header;
+
+    #[test]
+    fn header_either_way_round() {
+        let le = [b"P3D\xff", &0x10u32.to_le_bytes()[..], &0x20u32.to_le_bytes()].concat();
+        let be = [b"\xffD3P", &0x10u32.to_be_bytes()[..], &0x20u32.to_be_bytes()].concat();
+        for file in [le, be] {
+            let (_, header) = Header::from_bytes::<()>(&file, detect(&file)).unwrap();
+            assert_eq!(header, Header {id: 0x503344ff, ds: 0x10, cs: 0x20});
+        }
+    }
+
+    #[test]
+    fn u32_bytes_round_trip() {
+        for endian in [Endianness::Little, Endianness::Big] {
+            let bytes = u32_bytes(0x12345678, endian);
+            assert_eq!(u32::<&[u8], ()>(endian)(&bytes[..]), Ok((&[][..], 0x12345678)));
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+};
+use serde_json::{json, Value};
+
+use crate::col::{Root, X20};
+use crate::room::RoomCollision;
+use crate::shader::{Materials, Param, Shader};
+use crate::weird_chunks::{BBox, BSphere, X1d};
+use crate::utils::{Vec3f, RGBA};
+
+// accessor component types and buffer view targets from the glTF spec
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// a glTF 2.0 scene, the json and one .bin next to it
+#[derive(Debug, Default)]
+pub struct Gltf {
+    bin: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    scene: Vec<usize>,
+    materials: Vec<Value>,
+    images: Vec<Value>,
+    skins: Vec<Value>,
+    animations: Vec<Value>,
+    /// (p3d, shader name) -> material, png -> texture
+    material_ids: Vec<((String, String), usize)>,
+    texture_ids: Vec<(String, usize)>,
+}
+impl Gltf {
+    pub fn new() -> Gltf { Default::default() }
+
+    /// animation and inverse bind matrix data has no target
+    fn view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        self.bin.resize(self.bin.len().div_ceil(4) * 4, 0);
+        let mut view = json!({"buffer": 0, "byteOffset": self.bin.len(), "byteLength": bytes.len()});
+        if let Some(target) = target { view["target"] = json!(target); }
+        self.buffer_views.push(view);
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.len() - 1
+    }
+    fn accessor(&mut self, accessor: Value) -> usize {
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    /// VEC2/VEC3/VEC4 floats, positions need min and max so every one gets them
+    pub fn floats<const N: usize>(&mut self, data: &[[f32; N]]) -> usize {
+        let bytes: Vec<u8> = data.iter().flatten().flat_map(|f| f.to_le_bytes()).collect();
+        let view = self.view(&bytes, Some(ARRAY_BUFFER));
+        let min: Vec<f32> = (0..N).map(|i| data.iter().map(|d| d[i]).fold(f32::MAX, f32::min)).collect();
+        let max: Vec<f32> = (0..N).map(|i| data.iter().map(|d| d[i]).fold(f32::MIN, f32::max)).collect();
+        self.accessor(json!({
+            "bufferView": view, "componentType": FLOAT, "count": data.len(),
+            "type": format!("VEC{}", N), "min": min, "max": max,
+        }))
+    }
+    pub fn indices(&mut self, data: &[u32]) -> usize {
+        let bytes: Vec<u8> = data.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let view = self.view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+        self.accessor(json!({
+            "bufferView": view, "componentType": UNSIGNED_INT, "count": data.len(), "type": "SCALAR",
+        }))
+    }
+
+    /// animation times, which need min and max too
+    pub fn scalars(&mut self, data: &[f32]) -> usize {
+        let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let view = self.view(&bytes, None);
+        let min = data.iter().copied().fold(f32::MAX, f32::min);
+        let max = data.iter().copied().fold(f32::MIN, f32::max);
+        self.accessor(json!({
+            "bufferView": view, "componentType": FLOAT, "count": data.len(), "type": "SCALAR",
+            "min": [min], "max": [max],
+        }))
+    }
+    pub fn mat4s(&mut self, data: &[[f32; 16]]) -> usize {
+        let bytes: Vec<u8> = data.iter().flatten().flat_map(|f| f.to_le_bytes()).collect();
+        let view = self.view(&bytes, None);
+        self.accessor(json!({"bufferView": view, "componentType": FLOAT, "count": data.len(), "type": "MAT4"}))
+    }
+    pub fn joints(&mut self, data: &[[u16; 4]]) -> usize {
+        let bytes: Vec<u8> = data.iter().flatten().flat_map(|j| j.to_le_bytes()).collect();
+        let view = self.view(&bytes, Some(ARRAY_BUFFER));
+        self.accessor(json!({"bufferView": view, "componentType": UNSIGNED_SHORT, "count": data.len(), "type": "VEC4"}))
+    }
+    pub fn skin(&mut self, skin: Value) -> usize {
+        self.skins.push(skin);
+        self.skins.len() - 1
+    }
+    pub fn animation(&mut self, animation: Value) -> usize {
+        self.animations.push(animation);
+        self.animations.len() - 1
+    }
+
+    pub fn mesh(&mut self, name: &str, primitives: Vec<Value>) -> usize {
+        self.meshes.push(json!({"name": name, "primitives": primitives}));
+        self.meshes.len() - 1
+    }
+    /// adds a node, `root` ones go straight into the scene
+    pub fn node(&mut self, node: Value, root: bool) -> usize {
+        self.nodes.push(node);
+        if root { self.scene.push(self.nodes.len() - 1); }
+        self.nodes.len() - 1
+    }
+
+    pub fn add_child(&mut self, parent: usize, child: usize) {
+        let node = &mut self.nodes[parent];
+        if node.get("children").is_none() { node["children"] = json!([]); }
+        node["children"].as_array_mut().unwrap().push(json!(child));
+    }
+    /// a named node holding nothing, for grouping, under `parent` or in the
+    /// scene
+    pub fn group(&mut self, name: &str, extras: Value, parent: Option<usize>) -> usize {
+        let node = self.node(json!({"name": name, "extras": extras}), parent.is_none());
+        if let Some(parent) = parent { self.add_child(parent, node); }
+        node
+    }
+
+    fn texture(&mut self, png: &str) -> usize {
+        if let Some((_, i)) = self.texture_ids.iter().find(|(p, _)| p == png) { return *i; }
+        self.images.push(json!({"uri": png}));
+        let i = self.images.len() - 1;
+        self.texture_ids.push((png.to_string(), i));
+        i
+    }
+    /// the shader as a material, once per p3d and name, the diffuse colour
+    /// and texture go where glTF wants them and every param is kept in extras
+    pub fn material(&mut self, p3d: &str, shader: &Shader, materials: &Materials) -> usize {
+        let id = (p3d.to_string(), shader.name.clone());
+        if let Some((_, i)) = self.material_ids.iter().find(|(n, _)| *n == id) { return *i; }
+        let c = shader.color("DIFF").map(|c| c.float().to_one()).unwrap_or(RGBA {r: 1., g: 1., b: 1., a: 1.});
+        let mut pbr = json!({"baseColorFactor": [c.r, c.g, c.b, c.a], "metallicFactor": 0.0, "roughnessFactor": 1.0});
+        if let Some(png) = shader.texture().and_then(|t| materials.png(p3d, t)) {
+            pbr["baseColorTexture"] = json!({"index": self.texture(png)});
+        }
+        let params: serde_json::Map<String, Value> = shader.params.iter().map(|p| (p.name().to_string(), match p {
+            Param::Texture(_, t) => json!(t),
+            Param::Int(_, i) => json!(i),
+            Param::Float(_, f) => json!(f),
+            Param::Color(_, c) => json!([c.r, c.g, c.b, c.a]),
+        })).collect();
+        self.materials.push(json!({
+            "name": shader.mtl_name(),
+            "pbrMetallicRoughness": pbr,
+            "alphaMode": if shader.has_translucency != 0 { "BLEND" } else { "OPAQUE" },
+            "extras": {"pddi_shader": shader.pddi_shader, "vertex_needs": shader.vertex_needs, "params": params},
+        }));
+        let i = self.materials.len() - 1;
+        self.material_ids.push((id, i));
+        i
+    }
+    /// x20 from `p3d` with the material its shader name points at, when
+    /// there is one
+    pub fn x20_material(&mut self, x20: &X20, p3d: &str, materials: &Materials) -> Vec<Value> {
+        let mut primitives = self.x20(x20);
+        if let Some((file, shader)) = materials.shader(p3d, &x20.name) {
+            let material = self.material(file, shader, materials);
+            for primitive in primitives.iter_mut() {
+                primitive["material"] = json!(material);
+            }
+        }
+        primitives
+    }
+
+    /// a primitive per surface material
+    pub fn room(&mut self, room: &RoomCollision) -> Vec<Value> {
+        if room.verts.is_empty() || room.tris.is_empty() { return vec![]; }
+        let position = self.floats(&room.verts.iter().map(v3).collect::<Vec<_>>());
+        let mut materials: Vec<Option<u32>> = room.materials.iter().map(|m| Some(*m)).collect();
+        if materials.is_empty() { materials.push(None); }
+        materials.sort();
+        materials.dedup();
+        materials.into_iter().map(|material| {
+            let tris: Vec<[u32; 3]> = room.tris.iter().enumerate()
+                .filter(|(i, _)| material.is_none() || room.materials.get(*i) == material.as_ref())
+                .map(|(_, t)| *t)
+                .collect();
+            let indices = self.indices(tris.as_flattened());
+            json!({"attributes": {"POSITION": position}, "indices": indices, "mode": 4, "extras": {"surface": material}})
+        }).collect()
+    }
+
+    /// a primitive per index buffer with whatever the mesh chunk has, they
+    /// share the vertex attributes, positions and indices are the minimum,
+    /// empty without them
+    pub fn x20(&mut self, x20: &X20) -> Vec<Value> {
+        let Some(vb) = x20.vb.as_ref() else { return vec![] };
+        let tris: Vec<(u32, Vec<[u32; 3]>)> = x20.index_buffers().into_iter()
+            .map(|(id, ib)| (id, x20.triangles(ib)))
+            .filter(|(_, tris)| !tris.is_empty())
+            .collect();
+        let n = vb.inner.len();
+        if n == 0 || tris.is_empty() { return vec![]; }
+
+        let mut attributes = json!({});
+        attributes["POSITION"] = json!(self.floats(&vb.inner.iter().map(v3).collect::<Vec<_>>()));
+        if let Some(x06) = x20.x06.as_ref().filter(|x06| x06.normals.len() == n) {
+            let normals: Vec<[f32; 3]> = x06.normals.iter().map(|v| unit(v3(v))).collect();
+            attributes["NORMAL"] = json!(self.floats(&normals));
+        }
+        for (i, uvs) in x20.uvs.iter().filter(|uvs| uvs.inner.len() == n).enumerate() {
+            let uvs: Vec<[f32; 2]> = uvs.inner.iter().map(|uv| [uv.u, uv.v]).collect();
+            attributes[format!("TEXCOORD_{}", i)] = json!(self.floats(&uvs));
+        }
+        if let Some(vc) = x20.vc.as_ref().filter(|vc| vc.inner.len() == n) {
+            let colors: Vec<[f32; 4]> = vc.rbg2bgr().inner.iter().map(|c| {
+                let c = c.float().to_one();
+                [c.r, c.g, c.b, c.a]
+            }).collect();
+            attributes["COLOR_0"] = json!(self.floats(&colors));
+        }
+        if let Some((joints, weights)) = x20.skinning() {
+            attributes["JOINTS_0"] = json!(self.joints(&joints));
+            attributes["WEIGHTS_0"] = json!(self.floats(&weights));
+        }
+        let mut extras = json!({"shader": x20.name.replace("\x00", ""), "primitive": format!("{:?}", x20.primitive())});
+        if let Some(bbox) = &x20.bbox { extras["bbox"] = bbox_json(bbox); }
+        if let Some(bsphere) = &x20.bsphere { extras["bsphere"] = bsphere_json(bsphere); }
+
+        tris.into_iter().map(|(id, tris)| {
+            let indices = self.indices(tris.as_flattened());
+            let mut extras = extras.clone();
+            extras["indices_from"] = json!(format!("{:#010x}", id));
+            json!({"attributes": attributes, "indices": indices, "mode": 4, "extras": extras})
+        }).collect()
+    }
+
+    /// writes `path` and the .bin next to it
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let bin_path = path.with_extension("bin");
+        let mut gltf = json!({
+            "asset": {"version": "2.0", "generator": "crash_of_the_titans/p3d"},
+            "scene": 0,
+            "scenes": [{"nodes": self.scene}],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{
+                "uri": bin_path.file_name().unwrap().to_string_lossy(),
+                "byteLength": self.bin.len(),
+            }],
+        });
+        if !self.materials.is_empty() { gltf["materials"] = json!(self.materials); }
+        if !self.skins.is_empty() { gltf["skins"] = json!(self.skins); }
+        if !self.animations.is_empty() { gltf["animations"] = json!(self.animations); }
+        if !self.images.is_empty() {
+            gltf["images"] = json!(self.images);
+            gltf["samplers"] = json!([{}]);
+            gltf["textures"] = json!((0..self.images.len()).map(|i| json!({"source": i, "sampler": 0})).collect::<Vec<_>>());
+        }
+        OpenOptions::new().create(true).write(true).truncate(true).open(&bin_path)?.write_all(&self.bin)?;
+        OpenOptions::new().create(true).write(true).truncate(true).open(path)?
+            .write_all(serde_json::to_string_pretty(&gltf).unwrap().as_bytes())
+    }
+}
+
+fn v3(v: &Vec3f) -> [f32; 3] { [v.x, v.y, v.z] }
+fn bbox_json(bbox: &BBox) -> Value { json!({"min": v3(&bbox.min), "max": v3(&bbox.max)}) }
+fn bsphere_json(b: &BSphere) -> Value { json!({"center": v3(&b.center), "radius": b.radius}) }
+
+fn x1d_json(x: &X1d) -> Value {
+    json!({"null": x.null, "children": x.children.iter().map(x1d_json).collect::<Vec<_>>()})
+}
+
+/// the root's bounds and the chunks around its meshes, as they were read,
+/// for a node's extras
+pub fn root_extras(root: &Root) -> Value {
+    let x21 = root.x21.as_ref().map(|x| vec![x.null, x.thirty_two_1, x.thirty_two_2, x.thirty_two_3,
+        x.thirty_two_4, x.thirty_two_5, x.thirty_two_6, x.thirty_two_7]);
+    json!({
+        "bbox": bbox_json(&root.bbox),
+        "bsphere": bsphere_json(&root.bsphere),
+        "x21": x21,
+        "x00_20_12": root.x00_20_12.as_ref().map(|x| [x.f1, x.f2]),
+        "x01_20_12": root.x01_20_12.as_ref().map(|x| [x.u1, x.u2]),
+        "x17": root.x17.as_ref().map(|x| x.one),
+        "x1d": root.x1d.as_ref().map(x1d_json),
+    })
+}
+
+/// normalized, or straight up if it's zero
+pub fn unit([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let len = (x * x + y * y + z * z).sqrt();
+    if len == 0. { [0., 1., 0.] } else { [x / len, y / len, z / len] }
+}
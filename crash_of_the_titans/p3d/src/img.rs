@@ -7,20 +7,23 @@ use nom::{
     bytes::complete::{take},
     error::ParseError,
     multi::length_data,
-    number::complete::{le_f32, le_u8, le_u16, le_u32},
+    number::{Endianness, complete::{le_u8, le_u16, u32}},
     sequence::tuple,
 };
 
-pub const P3D: u32 =  0x503344FF;
-pub const TGA1: u32 = 0x05900100;
-pub const TGA2: u32 = 0x01900100;
-pub const TGA3: u32 = 0x02900100;
+use crate::endian::{self, u32_bytes};
+
+use std::fs::{create_dir_all, OpenOptions};
+use std::path::Path;
+
+use crate::chunk_ids::{TGA_2 as TGA2, TGA_3 as TGA3};
+use crate::chunks::{Header, children};
 
 // trait Paris<'a, E: ParseError<&'a [u8]> + Debug> {
 //     fn paris(&self) -> &dyn Parser<&'a [u8], Chunks<'a>, E>;
 // }
 trait Paris<'a, E: ParseError<&'a [u8]> + Debug> {
-    fn paris(&self) -> Box<dyn Parser<&'a [u8], Chunks<'a>, E>>;
+    fn paris(&self, endian: Endianness) -> Box<dyn Parser<&'a [u8], Chunks<'a>, E>>;
 }
 
 // #[derive(Debug)]
@@ -42,6 +45,24 @@ trait Paris<'a, E: ParseError<&'a [u8]> + Debug> {
 //     }
 // }
 
+/// 0x01900100, the name the png gets written under, the seven u32s line up
+/// with SHAR's image chunk so are probably version, width, height, bpp,
+/// palettized, has alpha and format
+#[derive(Debug, Default)]
+struct Tga2 {
+    file_name: String,
+    seven_strange_u32s: (u32, u32, u32, u32, u32, u32, u32),
+}
+impl Tga2 {
+    fn from_bytes<'a, E: ParseError<&'a [u8]> + Debug>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Tga2, E> {
+        let (input, file_name) = length_data(le_u8)(input)?;
+        let (input, seven_strange_u32s) =
+            tuple((u32(endian), u32(endian), u32(endian), u32(endian), u32(endian), u32(endian), u32(endian)))(input)?;
+        let file_name = String::from_utf8_lossy(file_name).replace("\x00", "");
+        Ok((input, Tga2 {file_name, seven_strange_u32s}))
+    }
+}
+
 #[derive(Debug, Default)]
 struct Tga3<'a> { png: PNG<'a>, }
 impl<'a> Tga3<'a> { fn new() -> Self { Default::default() }}
@@ -50,13 +71,13 @@ where
     E: ParseError<&'a [u8]> + Debug
 {
     // fn paris(&self) -> &dyn Parser<&'a [u8], Chunks<'a>, E> {
-    fn paris(&self) -> Box<dyn Parser<&'a [u8], Chunks<'a>, E>> {
+    fn paris(&self, endian: Endianness) -> Box<dyn Parser<&'a [u8], Chunks<'a>, E>> {
         // &move |input: &'a [u8]| {
         Box::new(move |input: &'a [u8]| {
             let (input, png_size) =
-                le_u32::<&'a [u8], E>(input).unwrap();
+                u32::<&'a [u8], E>(endian)(input)?;
             let (input, png) =
-                paris_png::<E>(png_size).parse(input).unwrap();
+                paris_png::<E>(png_size).parse(input)?;
 
             Ok((input, Chunks::Tga3(Tga3 {png})))
         })
@@ -80,14 +101,260 @@ enum Chunks<'a> {
 }
 impl<'a> Chunks<'a> {
     fn get_parser<E>(
-        id: u32
-    ) -> Rc<Box<dyn Parser<&'a [u8], Chunks<'a>, E>>>
+        id: u32, endian: Endianness
+    ) -> Box<dyn Parser<&'a [u8], Chunks<'a>, E>>
     where
         E: ParseError<&'a [u8]> + Debug
    {
         match id {
-            TGA3 => Rc::new(Tga3::new().paris()),
+            TGA3 => Tga3::new().paris(endian),
             _ => { panic!(" unknown id ") }
         }
     }
 }
+
+/// (texture name, png) for every 0x02900100 under a 0x01900100, the
+/// 0x00900100s above them only group them
+fn pngs<'a>(input: &'a [u8], endian: Endianness, name: &str, v: &mut Vec<(String, &'a [u8])>) {
+    let mut chunks = children(input, endian);
+    for (id, data, kids) in chunks.by_ref() {
+        match id {
+            TGA2 => match Tga2::from_bytes::<()>(data, endian) {
+                Ok((_, tga2)) => pngs(kids, endian, &tga2.file_name, v),
+                Err(_) => println!(" BAD {:08x} name", id),
+            },
+            TGA3 => match Chunks::get_parser::<()>(id, endian).parse(data) {
+                Ok((_, Chunks::Tga3(tga3))) => v.push((name.to_string(), tga3.png)),
+                Err(_) => println!(" BAD {:08x} png", id),
+            },
+            _ => pngs(kids, endian, name, v),
+        }
+    }
+    if let Ok((_, Header {id, ds, cs})) = Header::from_bytes::<()>(chunks.rest(), endian) {
+        println!(" BAD {:08x} ds={:#x} cs={:#x}", id, ds, cs);
+    }
+}
+
+/// writes every embedded png to `out_dir` as <texture name>.png, names that
+/// turn up more than once get a number, and textures.txt maps names to files
+/// returns the same (texture name, file name) pairs
+pub fn export(input: &[u8], out_dir: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut v: Vec<(String, &[u8])> = vec![];
+    pngs(input, endian::detect(input), "unnamed", &mut v);
+
+    create_dir_all(out_dir)?;
+    let mut index: Vec<String> = vec![];
+    let mut written: Vec<(String, String)> = vec![];
+    let mut used: Vec<String> = vec![];
+    for (name, png) in v.iter() {
+        let stem = Path::new(name).with_extension("").to_string_lossy().replace(['/', '\\'], "_");
+        let mut file_name = format!("{}.png", stem);
+        let mut n = 1;
+        while used.contains(&file_name.to_lowercase()) {
+            file_name = format!("{}_{}.png", stem, n);
+            n += 1;
+        }
+        used.push(file_name.to_lowercase());
+
+        if !png.starts_with(b"\x89PNG") {
+            println!(" BAD {} doesn't start with a png signature, written anyway", name);
+        }
+        OpenOptions::new().write(true).create(true).truncate(true)
+            .open(out_dir.join(&file_name))?.write_all(png)?;
+        println!(" GOOD {} {:?}", name, file_name);
+        index.push(format!("{}\t{}", name, file_name));
+        written.push((name.clone(), file_name));
+    }
+
+    OpenOptions::new().write(true).create(true).truncate(true)
+        .open(out_dir.join("textures.txt"))?.write_all((index.join("\n") + "\n").as_bytes())?;
+    println!("{} textures", v.len());
+    Ok(written)
+}
+
+/// what to swap in, from the png_dir given to `replace`
+struct Replacements {
+    dir: std::path::PathBuf,
+    /// textures.txt's (name, file) lines, used up in order so a name that's in
+    /// the file twice gets both of its pngs
+    index: Vec<(String, String, bool)>,
+}
+impl Replacements {
+    fn new(dir: &Path) -> Self {
+        let index = std::fs::read_to_string(dir.join("textures.txt")).unwrap_or_default()
+            .lines()
+            .filter_map(|l| l.split_once('\t'))
+            .map(|(name, file)| (name.to_string(), file.to_string(), false))
+            .collect();
+        Replacements {dir: dir.to_path_buf(), index}
+    }
+    /// the png for the next image called `name`, textures.txt first then
+    /// <name>.png
+    fn png(&mut self, name: &str) -> Option<(String, Vec<u8>)> {
+        let file = match self.index.iter_mut().find(|(n, _, used)| n == name && !used) {
+            Some((_, file, used)) => { *used = true; file.clone() }
+            None => format!("{}.png", Path::new(name).with_extension("").to_string_lossy().replace(['/', '\\'], "_")),
+        };
+        let png = std::fs::read(self.dir.join(&file)).ok()?;
+        Some((file, png))
+    }
+}
+
+/// width and height from a png's IHDR
+fn png_dimensions(png: &[u8]) -> Option<(u32, u32)> {
+    let ihdr = png.get(16..24)?;
+    Some((u32::from_be_bytes(ihdr[..4].try_into().ok()?), u32::from_be_bytes(ihdr[4..].try_into().ok()?)))
+}
+
+/// copies the chunks in `input` to `out`, swapping the png in every
+/// 0x02900100 there's a replacement for and redoing the sizes of everything
+/// around it, the ids and anything past the last chunk are kept as is
+fn rewrite(input: &[u8], endian: Endianness, name: &str, dims: Option<(u32, u32)>, r: &mut Replacements, out: &mut Vec<u8>, replaced: &mut Vec<String>) {
+    let mut chunks = children(input, endian);
+    loop {
+        let input = chunks.rest();
+        let Some((id, data, kids)) = chunks.next() else { break };
+        let cs = input.len() - chunks.rest().len();
+
+        let mut new_data: Vec<u8> = data.to_vec();
+        let mut new_children: Vec<u8> = vec![];
+        match id {
+            TGA2 => match Tga2::from_bytes::<()>(data, endian) {
+                Ok((_, tga2)) => {
+                    let (_, width, height, ..) = tga2.seven_strange_u32s;
+                    rewrite(kids, endian, &tga2.file_name, Some((width, height)), r, &mut new_children, replaced);
+                }
+                Err(_) => rewrite(kids, endian, name, dims, r, &mut new_children, replaced),
+            },
+            TGA3 => {
+                new_children = kids.to_vec();
+                if let (Ok((rest, png_size)), Some((file, png))) = (u32::<&[u8], ()>(endian)(data), r.png(name)) {
+                    // unchanged ones from an export are left alone
+                    if rest.get(..png_size as usize) == Some(png.as_slice()) {
+                        out.extend_from_slice(&input[..cs]);
+                        continue;
+                    }
+                    // anything after the png stays after it
+                    let after = rest.get(png_size as usize..).unwrap_or_default();
+                    new_data = [&u32_bytes(png.len() as u32, endian)[..], &png, after].concat();
+                    match (png_dimensions(&png), dims) {
+                        (Some(new), Some(old)) if new != old =>
+                            println!(" BAD {} {} is {}x{} but the image says {}x{}, written anyway", name, file, new.0, new.1, old.0, old.1),
+                        _ => println!(" GOOD {} <- {}", name, file),
+                    }
+                    replaced.push(name.to_string());
+                }
+            }
+            _ => rewrite(kids, endian, name, dims, r, &mut new_children, replaced),
+        }
+
+        let new_ds = 12 + new_data.len();
+        out.extend_from_slice(&input[..4]);
+        out.extend_from_slice(&u32_bytes(new_ds as u32, endian));
+        out.extend_from_slice(&u32_bytes((new_ds + new_children.len()) as u32, endian));
+        out.extend_from_slice(&new_data);
+        out.extend_from_slice(&new_children);
+    }
+    out.extend_from_slice(chunks.rest());
+}
+
+/// writes `input` to `out_path` with the pngs in `png_dir` in place of the
+/// embedded ones, keyed by texture name like `export` writes them, then
+/// reads it back to check only those changed, nothing's written if they
+/// didn't
+pub fn replace(input: &[u8], png_dir: &Path, out_path: &Path) -> io::Result<()> {
+    let mut r = Replacements::new(png_dir);
+    let mut out: Vec<u8> = vec![];
+    let mut replaced: Vec<String> = vec![];
+    let endian = endian::detect(input);
+    rewrite(input, endian, "unnamed", None, &mut r, &mut out, &mut replaced);
+
+    let (mut before, mut after) = (vec![], vec![]);
+    pngs(input, endian, "unnamed", &mut before);
+    pngs(&out, endian, "unnamed", &mut after);
+    let names = |v: &Vec<(String, &[u8])>| v.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>();
+    if names(&before) != names(&after) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the textures don't line up after rewriting"));
+    }
+    let mut r = Replacements::new(png_dir);
+    for ((name, old), (_, new)) in before.iter().zip(after.iter()) {
+        let expected = match r.png(name) {
+            Some((_, png)) => png,
+            None => old.to_vec(),
+        };
+        if *new != expected.as_slice() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} didn't come out as expected", name)));
+        }
+    }
+
+    OpenOptions::new().write(true).create(true).truncate(true).open(out_path)?.write_all(&out)?;
+    println!("{} of {} textures replaced", replaced.len(), before.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{read, remove_dir_all, write};
+    use crate::chunk_ids::{P3D, TGA_1};
+
+    /// a little endian chunk, the id as the tree prints it
+    fn chunk(id: u32, data: &[u8], kids: &[u8]) -> Vec<u8> {
+        let ds = 12 + data.len() as u32;
+        [&id.to_be_bytes()[..], &ds.to_le_bytes(), &(ds + kids.len() as u32).to_le_bytes(), data, kids].concat()
+    }
+    /// a 0x00900100 holding one named image and its png, `after` follows
+    /// the png in the 0x02900100
+    fn texture(name: &str, png: &[u8], after: &[u8]) -> Vec<u8> {
+        let tga2 = [&[name.len() as u8][..], name.as_bytes(), &[0; 28]].concat();
+        let tga3 = [&(png.len() as u32).to_le_bytes()[..], png, after].concat();
+        chunk(TGA_1, &[1, 2], &chunk(TGA2, &tga2, &chunk(TGA3, &tga3, &[])))
+    }
+    fn p3d(a: &[u8], b: &[u8]) -> Vec<u8> {
+        let kids = [texture("a.tga", a, &[7, 7]), chunk(0x01000100, &[9; 5], &[]), texture("b.tga", b, &[])].concat();
+        [chunk(P3D, &[], &kids), vec![0xee; 3]].concat()
+    }
+
+    #[test]
+    fn rewrite_fixes_every_size() {
+        let dir = std::env::temp_dir().join(format!("p3d_img_{}", std::process::id()));
+        _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let new_png = b"\x89PNG a longer png than before";
+        write(dir.join("a.png"), new_png).unwrap();
+
+        let input = p3d(b"\x89PNG old", b"\x89PNG b");
+        let (mut out, mut replaced) = (vec![], vec![]);
+        rewrite(&input, Endianness::Little, "unnamed", None, &mut Replacements::new(&dir), &mut out, &mut replaced);
+        assert_eq!(replaced, ["a.tga"]);
+        // the p3d, the 0x00900100 and the 0x01900100 above the png grow
+        // with it, the leftover bytes at the end stay
+        assert_eq!(out, p3d(new_png, b"\x89PNG b"));
+
+        let out_path = dir.join("out.p3d");
+        replace(&input, &dir, &out_path).unwrap();
+        let written = read(&out_path).unwrap();
+        _ = remove_dir_all(&dir);
+        assert_eq!(written, out);
+    }
+
+    #[test]
+    fn nothing_written_when_the_check_fails() {
+        let dir = std::env::temp_dir().join(format!("p3d_img_bad_{}", std::process::id()));
+        _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        write(dir.join("a.png"), b"\x89PNG new").unwrap();
+
+        // a png size past the end of the chunk doesn't read before the
+        // rewrite but does after it
+        let tga2 = [&[5][..], b"a.tga", &[0; 28]].concat();
+        let tga3 = [&100u32.to_le_bytes()[..], b"\x89PNG"].concat();
+        let input = chunk(P3D, &[], &chunk(TGA2, &tga2, &chunk(TGA3, &tga3, &[])));
+        let out_path = dir.join("out.p3d");
+        let e = replace(&input, &dir, &out_path).unwrap_err();
+        let exists = out_path.exists();
+        _ = remove_dir_all(&dir);
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(!exists);
+    }
+}
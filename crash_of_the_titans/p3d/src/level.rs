@@ -0,0 +1,186 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::Path,
+};
+use serde_json::json;
+
+use crate::chunks::{Chunks, chunk_paris, get_chunks};
+use crate::endian;
+use crate::gltf::{Gltf, root_extras};
+use crate::img;
+use crate::locator;
+use crate::room::room_collisions;
+use crate::shader::{Materials, shaders};
+
+/// L1_E1_R1_ext -> (L1_E1_R1, ext), files without a room number are their
+/// own room
+fn room_and_variant(stem: &str) -> (String, String) {
+    let parts: Vec<&str> = stem.split('_').collect();
+    let room = parts.iter().position(|p| {
+        let p = p.to_lowercase();
+        p.len() > 1 && p.starts_with('r') && p[1..].chars().all(|c| c.is_ascii_digit())
+    });
+    match room {
+        Some(i) => (parts[..=i].join("_"), parts[i + 1..].join("_")),
+        None => (stem.to_string(), String::new()),
+    }
+}
+
+/// every p3d in a level package, a dir or anything `vfs` opens, as one
+/// scene: level > room > render/collision > variant > root > X20
+/// `_col` variants and room_collision go on the collision layer, the rest
+/// on the render one, locators go in an entities node per room and meshes
+/// get the material their shader name points at, their own p3d's first
+pub fn assemble(package: &Path, out_path: &Path) -> io::Result<()> {
+    let archive = vfs::open_archive(package)?;
+    let mut p3ds: Vec<String> = archive.list().into_iter().filter(|p| p.to_lowercase().ends_with(".p3d")).collect();
+    p3ds.sort();
+
+    // shaders and textures are shared across the package, textures go in
+    // <out>_textures/<p3d>/ so names can repeat between files
+    let mut materials = Materials::default();
+    let texture_dir = format!("{}_textures", out_path.file_stem().unwrap_or_default().to_string_lossy());
+    for p3d in p3ds.iter() {
+        let buf = archive.read(p3d)?;
+        let mut found = vec![];
+        shaders(&buf, &mut found);
+        let stem = Path::new(p3d).file_stem().unwrap().to_string_lossy().to_string();
+        let dir = format!("{}/{}", texture_dir, stem);
+        let written = img::export(&buf, &out_path.with_file_name(&dir))?;
+        materials.add(p3d, found, written.into_iter().map(|(name, file_name)| (name, format!("{}/{}", dir, file_name))).collect());
+    }
+
+    let mut gltf = Gltf::new();
+    let level_name = package.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let level = gltf.group(&level_name, json!({}), None);
+    // room -> (node, render layer, collision layer)
+    let mut rooms: BTreeMap<String, (usize, usize, usize)> = BTreeMap::new();
+
+    for p3d in p3ds.iter() {
+        // a p3d that doesn't parse is left out, nothing's added for it
+        let buf = archive.read(p3d)?;
+        let input: &[u8] = &buf;
+        let chunks = match chunk_paris::<()>(input, endian::detect(input)) {
+            Ok((_, tree)) => get_chunks(&tree, &mut vec![]),
+            Err(_) => {
+                println!(" BAD {} didn't parse, skipped", p3d);
+                continue;
+            }
+        };
+
+        let stem = Path::new(p3d).file_stem().unwrap().to_string_lossy().to_string();
+        let (room_name, variant) = room_and_variant(&stem);
+        let &mut (room, render, collision) = rooms.entry(room_name.clone()).or_insert_with(|| {
+            let room = gltf.group(&room_name, json!({}), Some(level));
+            let render = gltf.group("render", json!({"layer": "render"}), Some(room));
+            let collision = gltf.group("collision", json!({"layer": "collision"}), Some(room));
+            (room, render, collision)
+        });
+
+        let layer_name = if variant.to_lowercase().ends_with("col") { "collision" } else { "render" };
+        let layer = if layer_name == "collision" { collision } else { render };
+        let file = gltf.group(&stem, json!({"layer": layer_name, "path": p3d}), Some(layer));
+
+        for c in chunks.iter() {
+            let Chunks::Col(root, x20s) = c else { continue };
+            let root_node = gltf.group(&root.name.replace('\0', ""), root_extras(root), Some(file));
+            for x20 in x20s.iter() {
+                let name = x20.name.replace('\0', "");
+                let primitives = gltf.x20_material(x20, p3d, &materials);
+                if primitives.is_empty() { continue; }
+                let mesh = gltf.mesh(&name, primitives);
+                let node = gltf.node(json!({"name": name, "mesh": mesh, "extras": {"layer": layer_name}}), false);
+                gltf.add_child(root_node, node);
+            }
+        }
+
+        let mut rcs = vec![];
+        room_collisions(input, &mut rcs);
+        for (i, rc) in rcs.iter().enumerate() {
+            let primitives = gltf.room(rc);
+            if primitives.is_empty() { continue; }
+            let name = format!("{}_room_collision_{}", stem, i);
+            let mesh = gltf.mesh(&name, primitives);
+            let node = gltf.node(json!({"name": name, "mesh": mesh, "extras": {"layer": "collision"}}), false);
+            gltf.add_child(collision, node);
+        }
+        let mut entities = vec![];
+        locator::entities(input, &mut entities);
+        locator::markers(&mut gltf, &entities, Some(room));
+        println!(" GOOD {} -> {}/{}", p3d, room_name, layer_name);
+    }
+
+    println!("{} p3ds, {} rooms", p3ds.len(), rooms.len());
+    gltf.write(out_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+
+    #[test]
+    fn rooms_and_variants() {
+        assert_eq!(room_and_variant("L1_E1_R1_ext"), ("L1_E1_R1".to_string(), "ext".to_string()));
+        assert_eq!(room_and_variant("L1_E1_R12__col"), ("L1_E1_R12".to_string(), "_col".to_string()));
+        assert_eq!(room_and_variant("L1_E1_R2"), ("L1_E1_R2".to_string(), String::new()));
+        assert_eq!(room_and_variant("L1_E1_Rx_ext"), ("L1_E1_Rx_ext".to_string(), String::new()));
+        assert_eq!(room_and_variant("frontend"), ("frontend".to_string(), String::new()));
+    }
+
+    /// a little endian chunk, the id as the tree prints it
+    fn chunk(id: u32, data: &[u8], kids: &[u8]) -> Vec<u8> {
+        let ds = 12 + data.len() as u32;
+        [&id.to_be_bytes()[..], &ds.to_le_bytes(), &(ds + kids.len() as u32).to_le_bytes(), data, kids].concat()
+    }
+    /// a p3d with one root and no meshes
+    fn p3d(root: &str) -> Vec<u8> {
+        let data = [&[root.len() as u8][..], root.as_bytes(), &[0; 8]].concat();
+        chunk(crate::chunk_ids::P3D, &[], &chunk(crate::chunk_ids::ROOT, &data, &[]))
+    }
+
+    /// the names under a node, by name, depth first
+    fn names(nodes: &[Value], i: usize, depth: usize, v: &mut Vec<String>) {
+        v.push(format!("{}{}", "  ".repeat(depth), nodes[i]["name"].as_str().unwrap()));
+        for c in nodes[i]["children"].as_array().into_iter().flatten() {
+            names(nodes, c.as_u64().unwrap() as usize, depth + 1, v);
+        }
+    }
+
+    #[test]
+    fn hierarchy_and_bad_files() {
+        let root = std::env::temp_dir().join(format!("p3d_level_{}", std::process::id()));
+        _ = remove_dir_all(&root);
+        create_dir_all(root.join("L1_E1")).unwrap();
+        write(root.join("L1_E1/L1_E1_R1_ext.p3d"), p3d("ext")).unwrap();
+        write(root.join("L1_E1/L1_E1_R1__col.p3d"), p3d("col")).unwrap();
+        write(root.join("L1_E1/L1_E1_R2.p3d"), p3d("r2")).unwrap();
+        // the root's name runs off the end
+        write(root.join("L1_E1/L1_E1_R3.p3d"), chunk(crate::chunk_ids::P3D, &[], &chunk(crate::chunk_ids::ROOT, &[200, b'x'], &[]))).unwrap();
+        let out = root.join("out.gltf");
+        assemble(&root.join("L1_E1"), &out).unwrap();
+
+        let gltf: Value = serde_json::from_str(&read_to_string(&out).unwrap()).unwrap();
+        _ = remove_dir_all(&root);
+        let nodes = gltf["nodes"].as_array().unwrap();
+        let mut v = vec![];
+        names(nodes, gltf["scenes"][0]["nodes"][0].as_u64().unwrap() as usize, 0, &mut v);
+        assert_eq!(v, [
+            "L1_E1",
+            "  L1_E1_R1",
+            "    render",
+            "      L1_E1_R1_ext",
+            "        ext",
+            "    collision",
+            "      L1_E1_R1__col",
+            "        col",
+            "  L1_E1_R2",
+            "    render",
+            "      L1_E1_R2",
+            "        r2",
+            "    collision",
+        ]);
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    fmt::Debug,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+};
+use nom::{
+    IResult,
+    error::ParseError,
+    multi::count,
+    number::{Endianness, complete::{f32, u32}},
+    sequence::tuple,
+};
+use serde_json::{json, Value};
+
+use crate::chunk_ids::{LOCATOR, TRIGGER_VOLUME, P3D_LOCATOR};
+use crate::chunks::{FromBytes, children};
+use crate::endian;
+use crate::gltf::Gltf;
+use crate::utils::{Vec3f, pstring};
+
+/// what a locator is, from its type in SHAR's numbering (triggers.rs's
+/// Ttype) until we know CotT's, with what the params hold for the types
+/// that say
+#[derive(Debug, PartialEq, Clone)]
+pub enum Kind {
+    /// the first param is which event
+    Event(Option<u32>),
+    Script,
+    Generic,
+    CarStart,
+    Spline,
+    /// the params are the zone's name
+    DynamicZone(Option<String>),
+    Occlusion,
+    InteriorEntrance,
+    Directional,
+    Action,
+    Fov,
+    BreakableCamera,
+    StaticCamera,
+    PedGroup,
+    Coin,
+    SpawnPoint,
+    /// past the end of SHAR's types
+    Unknown(u32),
+    /// pure3d's plain locator, only a position
+    Marker,
+}
+impl Kind {
+    fn new(type_id: Option<u32>, params: &[u32], text: Option<&str>) -> Kind {
+        let Some(type_id) = type_id else { return Kind::Marker };
+        match type_id {
+            0 => Kind::Event(params.first().copied()),
+            1 => Kind::Script,
+            2 => Kind::Generic,
+            3 => Kind::CarStart,
+            4 => Kind::Spline,
+            5 => Kind::DynamicZone(text.map(str::to_string)),
+            6 => Kind::Occlusion,
+            7 => Kind::InteriorEntrance,
+            8 => Kind::Directional,
+            9 => Kind::Action,
+            10 => Kind::Fov,
+            11 => Kind::BreakableCamera,
+            12 => Kind::StaticCamera,
+            13 => Kind::PedGroup,
+            14 => Kind::Coin,
+            15 => Kind::SpawnPoint,
+            t => Kind::Unknown(t),
+        }
+    }
+    pub fn name(&self) -> String {
+        match self {
+            Kind::Event(_) => "event",
+            Kind::Script => "script",
+            Kind::Generic => "generic",
+            Kind::CarStart => "car_start",
+            Kind::Spline => "spline",
+            Kind::DynamicZone(_) => "dynamic_zone",
+            Kind::Occlusion => "occlusion",
+            Kind::InteriorEntrance => "interior_entrance",
+            Kind::Directional => "directional",
+            Kind::Action => "action",
+            Kind::Fov => "fov",
+            Kind::BreakableCamera => "breakable_camera",
+            Kind::StaticCamera => "static_camera",
+            Kind::PedGroup => "ped_group",
+            Kind::Coin => "coin",
+            Kind::SpawnPoint => "spawn_point",
+            Kind::Unknown(t) => return format!("locator_{}", t),
+            Kind::Marker => "marker",
+        }.to_string()
+    }
+}
+
+/// what the name looks like it's for, crates, checkpoints and enemies
+/// aren't types of their own in SHAR's numbering, so this is only a guess
+/// and kept apart from `Kind`
+fn name_guess(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    if name.contains("crate") || name.contains("box") { return Some("crate"); }
+    if name.contains("checkpoint") || name.starts_with("cp_") { return Some("checkpoint"); }
+    if name.contains("enemy") || name.contains("spawn") { return Some("enemy"); }
+    None
+}
+
+/// 0x06000003, a box or sphere, its matrix is in world space
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Volume {
+    pub name: String,
+    /// 0 sphere, 1 box in SHAR
+    pub shape: u32,
+    pub scale: Vec3f,
+    pub matrix: [f32; 16],
+}
+impl FromBytes for Volume {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Volume, E>
+        where
+            E: ParseError<&'a [u8]> + Debug, {
+        let (input, (name, shape, scale)) = tuple((pstring, u32(endian), |i| Vec3f::from_bytes(i, endian)))(input)?;
+        let (input, matrix) = count(f32(endian), 16)(input)?;
+        Ok((input, Volume {name, shape, scale, matrix: matrix.try_into().unwrap()}))
+    }
+}
+
+/// a placed thing, 0x05000003 is laid out like SHAR's locator, name, type,
+/// type specific data, position and trigger volumes, and 0x00400100 like
+/// pure3d's plain one, a name and a position
+#[derive(Debug, PartialEq, Clone)]
+pub struct Entity {
+    pub name: String,
+    pub kind: Kind,
+    /// from the name, see `name_guess`
+    pub name_guess: Option<&'static str>,
+    /// None for pure3d's plain locator
+    pub type_id: Option<u32>,
+    pub position: Vec3f,
+    /// the type specific u32s
+    pub params: Vec<u32>,
+    /// the params as a string when they're all printable, SHAR keeps zone
+    /// names and the like there
+    pub text: Option<String>,
+    pub volumes: Vec<Volume>,
+}
+impl Entity {
+    fn locator<'a, E: ParseError<&'a [u8]> + Debug>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Entity, E> {
+        let (input, (name, type_id, num)) = tuple((pstring, u32(endian), u32(endian)))(input)?;
+        let (input, params) = count(u32(endian), num as usize)(input)?;
+        let (input, position) = Vec3f::from_bytes(input, endian)?;
+        // the volumes follow as chunks of their own, the count's only a hint
+        let (input, _num_volumes) = u32(endian)(input)?;
+        let type_id = Some(type_id);
+        let text = text(&params, endian);
+        let kind = Kind::new(type_id, &params, text.as_deref());
+        let name_guess = name_guess(&name);
+        Ok((input, Entity {name, kind, name_guess, type_id, position, params, text, volumes: vec![]}))
+    }
+    fn p3d_locator<'a, E: ParseError<&'a [u8]> + Debug>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Entity, E> {
+        let (input, (name, position)) = tuple((pstring, |i| Vec3f::from_bytes(i, endian)))(input)?;
+        let name_guess = name_guess(&name);
+        Ok((input, Entity {name, kind: Kind::Marker, name_guess, type_id: None, position, params: vec![], text: None, volumes: vec![]}))
+    }
+
+    pub fn json(&self) -> Value {
+        let v3 = |v: &Vec3f| [v.x, v.y, v.z];
+        let mut json = json!({
+            "name": self.name,
+            "kind": self.kind.name(),
+            "name_guess": self.name_guess,
+            "type": self.type_id,
+            "position": v3(&self.position),
+            "params": self.params,
+            "text": self.text,
+            "volumes": self.volumes.iter().map(|v| json!({
+                "name": v.name, "shape": v.shape, "scale": v3(&v.scale), "matrix": v.matrix,
+            })).collect::<Vec<_>>(),
+        });
+        match &self.kind {
+            Kind::Event(Some(event)) => json["event"] = json!(event),
+            Kind::DynamicZone(Some(zone)) => json["zone"] = json!(zone),
+            _ => (),
+        }
+        json
+    }
+}
+
+/// the params' bytes in the order they were read
+fn text(params: &[u32], endian: Endianness) -> Option<String> {
+    let bytes: Vec<u8> = params.iter().flat_map(|p| endian::u32_bytes(*p, endian)).collect();
+    let text = String::from_utf8_lossy(&bytes).trim_end_matches('\0').to_string();
+    (!text.is_empty() && text.chars().all(|c| c.is_ascii_graphic() || c == ' ')).then_some(text)
+}
+
+/// every locator in the file with its trigger volumes
+pub fn entities(input: &[u8], v: &mut Vec<Entity>) {
+    entities_under(input, endian::detect(input), v);
+}
+
+fn entities_under(input: &[u8], endian: Endianness, v: &mut Vec<Entity>) {
+    for (id, data, kids) in children(input, endian) {
+        let entity = match id {
+            LOCATOR => Some(Entity::locator::<()>(data, endian)),
+            P3D_LOCATOR => Some(Entity::p3d_locator::<()>(data, endian)),
+            _ => None,
+        };
+        match entity {
+            Some(Ok((_, mut entity))) => {
+                for (_, data, _) in children(kids, endian).filter(|c| c.0 == TRIGGER_VOLUME) {
+                    match Volume::from_bytes::<()>(data, endian) {
+                        Ok((_, volume)) => entity.volumes.push(volume),
+                        Err(_) => println!(" BAD {} trigger volume", entity.name),
+                    }
+                }
+                v.push(entity);
+            }
+            Some(Err(_)) => println!(" BAD locator {:08x}", id),
+            None => entities_under(kids, endian, v),
+        }
+    }
+}
+
+pub fn json_write(entities: &[Entity], out_path: &Path) -> io::Result<()> {
+    let json = json!(entities.iter().map(Entity::json).collect::<Vec<_>>());
+    OpenOptions::new().create(true).write(true).truncate(true).open(out_path)?
+        .write_all(serde_json::to_string_pretty(&json).unwrap().as_bytes())
+}
+
+/// an empty node per entity at its position, volumes are children with
+/// their matrix moved to be relative to it
+pub fn markers(gltf: &mut Gltf, entities: &[Entity], parent: Option<usize>) {
+    if entities.is_empty() { return; }
+    let group = gltf.group("entities", json!({"layer": "entities"}), parent);
+    for entity in entities.iter() {
+        let p = &entity.position;
+        let mut extras = entity.json();
+        extras.as_object_mut().unwrap().remove("volumes");
+        let node = gltf.node(json!({"name": entity.name, "translation": [p.x, p.y, p.z], "extras": extras}), false);
+        gltf.add_child(group, node);
+        for volume in entity.volumes.iter() {
+            let mut matrix = volume.matrix;
+            (matrix[12], matrix[13], matrix[14]) = (matrix[12] - p.x, matrix[13] - p.y, matrix[14] - p.z);
+            let child = gltf.node(json!({
+                "name": volume.name, "matrix": matrix,
+                "extras": {"shape": volume.shape, "scale": [volume.scale.x, volume.scale.y, volume.scale.z]},
+            }), false);
+            gltf.add_child(node, child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locator(name: &str, type_id: u32, params: &[u8]) -> Vec<u8> {
+        let mut v = vec![name.len() as u8];
+        v.extend(name.as_bytes());
+        for u in [type_id, params.len() as u32 / 4] { v.extend(u.to_le_bytes()); }
+        v.extend(params);
+        for f in [1f32, 2., 3.] { v.extend(f.to_le_bytes()); }
+        v.extend(0u32.to_le_bytes());
+        v
+    }
+
+    #[test]
+    fn kind_from_type_and_params() {
+        let (_, zone) = Entity::locator::<()>(&locator("crate_zone", 5, b"z1\0\0"), Endianness::Little).unwrap();
+        assert_eq!(zone.kind, Kind::DynamicZone(Some("z1".to_string())));
+        // the name only goes in the guess
+        assert_eq!(zone.name_guess, Some("crate"));
+        assert_eq!(zone.position, Vec3f {x: 1., y: 2., z: 3.});
+
+        let (_, event) = Entity::locator::<()>(&locator("cp_1", 0, &7u32.to_le_bytes()), Endianness::Little).unwrap();
+        assert_eq!(event.kind, Kind::Event(Some(7)));
+        assert_eq!(event.name_guess, Some("checkpoint"));
+        assert_eq!(event.json()["event"], json!(7));
+    }
+
+    #[test]
+    fn types_past_shars() {
+        assert_eq!(Kind::new(Some(15), &[], None), Kind::SpawnPoint);
+        assert_eq!(Kind::new(Some(40), &[], None).name(), "locator_40");
+        assert_eq!(Kind::new(None, &[1], None), Kind::Marker);
+        assert_eq!(name_guess("thing"), None);
+    }
+}
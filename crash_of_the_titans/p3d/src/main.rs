@@ -3,43 +3,120 @@ use std::fs::{File, OpenOptions, create_dir};
 use std::io::{self, prelude::*, Read, Write};
 use std::path::PathBuf;
 
-mod chunks; mod col; mod obj; mod utils; mod weird_chunks;
+mod chunk_ids; mod chunks; mod col; mod endian; mod gltf; mod img; mod level; mod locator; mod obj; mod room; mod shader; mod skin; mod tree; mod utils; mod weird_chunks;
 use chunks::{Chunks, chunk_paris, get_chunks};
+use gltf::Gltf;
 use obj::Obj;
 
 fn main() -> io::Result<()> {
-    let path = PathBuf::from(
+    let args: Vec<String> = std::env::args().collect();
+    match args.as_slice() {
+        [_, mode, path, rest @ ..] if mode == "tree" => {
+            let ids_only = rest.iter().any(|a| a == "--ids");
+            let mut lines: Vec<String> = vec![];
+            let buf = vfs::read_path(&PathBuf::from(path))?;
+            tree::tree(&buf, endian::detect(&buf), 0, 0, ids_only, &mut lines);
+            println!("{}", lines.join("\n"));
+            return Ok(());
+        }
+        [_, mode, path, out_dir] if mode == "textures" => {
+            let buf = vfs::read_path(&PathBuf::from(path))?;
+            return img::export(&buf, &PathBuf::from(out_dir)).map(|_| ());
+        }
+        // animations are often kept apart from the rig, so any number of
+        // p3ds after the first are read in too
+        [_, mode, path, out_path, more @ ..] if mode == "character" => {
+            let mut character = skin::Character::default();
+            for path in std::iter::once(path).chain(more) {
+                let buf = vfs::read_path(&PathBuf::from(path))?;
+                character.read(&buf);
+            }
+            return character.export(&PathBuf::from(out_path));
+        }
+        // pngs named like `textures` writes them, its textures.txt too
+        [_, mode, path, png_dir, out_path] if mode == "replace" => {
+            let buf = vfs::read_path(&PathBuf::from(path))?;
+            return img::replace(&buf, &PathBuf::from(png_dir), &PathBuf::from(out_path));
+        }
+        [_, mode, package, out_path] if mode == "level" => {
+            return level::assemble(&PathBuf::from(package), &PathBuf::from(out_path));
+        }
+        _ => (),
+    }
+    // either an extracted p3d or one inside an rcf,
+    // /tmp/cott/default.rcf/package/L1_E1/L1_E1_R1_ext.p3d
+    let path = PathBuf::from(match args.get(1) {
+        Some(path) => path.as_str(),
         // "/tmp/cott/thing2.p3d",
-        "/tmp/cott/default/package/L1_E1/L1_E1_R1_ext.p3d"
-    );
-    let mut buf: Vec<u8> = vec![];
-    let mut file = OpenOptions::new()
-        .read(true).open(&path)?.read_to_end(&mut buf).unwrap();
+        None => "/tmp/cott/default/package/L1_E1/L1_E1_R1_ext.p3d",
+    });
+    let buf: Vec<u8> = vfs::read_path(&path)?;
     let input = buf.as_slice();
+    let endian = endian::detect(input);
+    println!("{:?} endian", endian);
     // let input = &input[0x227548..];
     
-    let chunks = get_chunks(
-        &chunk_paris::<()>(input).unwrap().1,
-        &mut vec![]
-    );
+    let (_, tree) = chunk_paris::<()>(input, endian)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{:?} didn't parse", path)))?;
+    let chunks = get_chunks(&tree, &mut vec![]);
+
+    let out_path = PathBuf::from(path.file_stem().unwrap().to_string_lossy().to_string());
+    let stem = out_path.to_string_lossy().to_string();
+
+    // pngs in <stem>_textures/ and the shaders pointing at them
+    let p3d = path.to_string_lossy().to_string();
+    let mut materials = shader::Materials::default();
+    let mut shaders = vec![];
+    shader::shaders(input, &mut shaders);
+    let texture_dir = format!("{}_textures", stem);
+    let textures = img::export(input, &PathBuf::from(&texture_dir))?
+        .into_iter()
+        .map(|(name, file_name)| (name, format!("{}/{}", texture_dir, file_name)))
+        .collect();
+    materials.add(&p3d, shaders, textures);
+    OpenOptions::new().create(true).write(true).truncate(true)
+        .open(out_path.with_extension("mtl"))?.write_all(materials.mtl().as_bytes())?;
 
     let mut obj = Obj::new();
+    obj.mtllib(&format!("{}.mtl", stem));
+    let mut gltf = Gltf::new();
     for c in chunks.iter() {
         if let Chunks::Col(root, x20s) = c {
             // _ = create_dir(&dir);
 
+            let mut primitives = vec![];
             for x20 in x20s.iter() {
                 println!("{:?}", &x20.name.replace("\x00", ""));
 
                 obj.comment(&root.name);
+                // even when there's no shader, or the last one carries on
+                obj.usemtl(&shader::mtl_name(&x20.name));
                 obj.x20(x20);
+                primitives.extend(gltf.x20_material(x20, &p3d, &materials));
+            }
+            if !primitives.is_empty() {
+                let name = root.name.replace("\x00", "");
+                let mesh = gltf.mesh(&name, primitives);
+                gltf.node(serde_json::json!({"name": name, "mesh": mesh, "extras": gltf::root_extras(root)}), true);
             }
             // println!("{:?}", &dir);
         }
     }
-    obj.obj_write(PathBuf::from(
-        path.file_stem().unwrap().to_string_lossy().to_string()
-    ).with_extension("obj"));
+    // enemies, crates, checkpoints and triggers
+    let mut entities = vec![];
+    locator::entities(input, &mut entities);
+    locator::json_write(&entities, &PathBuf::from(format!("{}_entities.json", stem)))?;
+    locator::markers(&mut gltf, &entities, None);
+
+    obj.obj_write(out_path.with_extension("obj"));
+    gltf.write(&out_path.with_extension("gltf"))?;
+
+    // beside the _col objs in stuff/ to compare against
+    let mut rooms = vec![];
+    room::room_collisions(input, &mut rooms);
+    for (i, room) in rooms.iter().enumerate() {
+        room.obj_write(&PathBuf::from(format!("{}_room_col_{}.obj", stem, i)))?;
+    }
 
     Ok(())
 }
@@ -5,12 +5,16 @@ use std::{
     path::PathBuf,
 };
 
-use crate::col::{X20, Vb, Vc, Ib};
+use crate::col::{X20, Vb, Vc, UvSet};
+use crate::gltf::unit;
+use crate::weird_chunks::X06;
 use crate::utils::{Vec3f, RGBA};
 
 #[derive(Debug, Default)]
 pub struct Obj {
     v: u32,
+    vt: u32,
+    vn: u32,
     s: String,
 }
 impl Obj {
@@ -24,6 +28,14 @@ impl Obj {
         self.s += &format!("# {comment}\n"); 
         self
     }
+    pub fn mtllib(&mut self, mtl: &str) -> &mut Self {
+        self.s += &format!("mtllib {mtl}\n");
+        self
+    }
+    pub fn usemtl(&mut self, name: &str) -> &mut Self {
+        self.s += &format!("usemtl {name}\n");
+        self
+    }
     pub fn add_v(&mut self, vert: &Vec3f) -> u32 {
         self.s += &format!("v {} {} {}\n", vert.x, vert.y, vert.z);
         self.v += 1;
@@ -72,15 +84,32 @@ impl Obj {
         }
         self
     }
-    pub fn obj_ib(&mut self, ib: &Ib, offset: u32) -> &mut Self {
-        for (i, _) in ib.inner[..ib.inner.len()-2].iter().enumerate() {
-            // if i+2 >= ib.inner.len() { break; }
-            if i % 3 != 0 { continue; }
-            self.s += &format!("f {} {} {}\n",
-                ib.inner[i]+1 + offset,
-                ib.inner[i+1]+1 + offset,
-                ib.inner[i+2]+1 + offset, 
-            );
+    /// obj's v runs bottom up, the uvs are top down like d3d
+    pub fn obj_vt(&mut self, uvs: &UvSet) -> &mut Self {
+        for uv in uvs.inner.iter() {
+            self.s += &format!("vt {} {}\n", uv.u, 1. - uv.v);
+            self.vt += 1;
+        }
+        self
+    }
+    pub fn obj_vn(&mut self, x06: &X06) -> &mut Self {
+        for n in x06.normals.iter() {
+            let [x, y, z] = unit([n.x, n.y, n.z]);
+            self.s += &format!("vn {} {} {}\n", x, y, z);
+            self.vn += 1;
+        }
+        self
+    }
+    /// `vt`/`vn` are where this mesh's uvs and normals start, if it has them
+    pub fn obj_ib(&mut self, tris: &[[u32; 3]], offset: u32, vt: Option<u32>, vn: Option<u32>) -> &mut Self {
+        let corner = |i: u32| match (vt, vn) {
+            (Some(t), Some(n)) => format!("{}/{}/{}", i + 1 + offset, i + 1 + t, i + 1 + n),
+            (Some(t), None) => format!("{}/{}", i + 1 + offset, i + 1 + t),
+            (None, Some(n)) => format!("{}//{}", i + 1 + offset, i + 1 + n),
+            (None, None) => format!("{}", i + 1 + offset),
+        };
+        for tri in tris {
+            self.s += &format!("f {} {} {}\n", corner(tri[0]), corner(tri[1]), corner(tri[2]));
         }
         self
     }
@@ -89,15 +118,35 @@ impl Obj {
         let len = self.v;
 
         if let Some(vb) = &x20.vb {
-            if let Some(ib) = &x20.ib {
+            if !x20.index_buffers().is_empty() {
 
                 if let Some(vc) = &x20.vc{
                     self.comment(&x20.name);
+                    if let Some(b) = &x20.bbox {
+                        self.comment(format!("bbox {} {} {}  {} {} {}", b.min.x, b.min.y, b.min.z, b.max.x, b.max.y, b.max.z));
+                    }
+                    if let Some(b) = &x20.bsphere {
+                        self.comment(format!("bsphere {} {} {}  {}", b.center.x, b.center.y, b.center.z, b.radius));
+                    }
 
                     // self.obj_vb(&vb);
                     self.obj_vb_vc(&vb, &vc.rbg2bgr());
 
-                    self.obj_ib(&ib, len);
+                    // only the first uv set, obj has nowhere to put the rest
+                    let n = vb.inner.len();
+                    let vt = match x20.uvs.iter().find(|uvs| uvs.inner.len() == n) {
+                        Some(uvs) => { let vt = self.vt; self.obj_vt(uvs); Some(vt) }
+                        None => None,
+                    };
+                    let vn = match x20.x06.as_ref().filter(|x06| x06.normals.len() == n) {
+                        Some(x06) => { let vn = self.vn; self.obj_vn(x06); Some(vn) }
+                        None => None,
+                    };
+
+                    for (id, ib) in x20.index_buffers() {
+                        self.comment(format!("indices from {:#010x}", id));
+                        self.obj_ib(&x20.triangles(ib), len, vt, vn);
+                    }
                 } else {}
 
             } else {}
@@ -105,4 +154,4 @@ impl Obj {
 
         self
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,222 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+};
+use nom::{
+    IResult,
+    multi::count,
+    number::{Endianness, complete::{le_u8, u16, u32}},
+    sequence::tuple,
+};
+
+use crate::chunk_ids::{
+    ROOM_COLLISION, SATURATED_DATA, SURFACE_MAT_DATA,
+    X0A_00_01_07 as MESH, X03_00_00_07 as TRIS, X06_00_00_07 as VERTS,
+};
+use crate::chunks::children;
+use crate::endian;
+use crate::utils::Vec3f;
+
+/// 0x25000107 > 0x01000107 > 0x0a000107, one per 0x0a000107, its
+/// 0x00600107 (SaturatedData) child holds the 0x12600107 SurfaceMatData
+/// the layouts are what `verts`, `tris` and `surface_materials` read and
+/// have only been checked against the one level, a chunk that doesn't fit
+/// them exactly is printed BAD instead of read some other way
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RoomCollision {
+    pub verts: Vec<Vec3f>,
+    pub tris: Vec<[u32; 3]>,
+    /// one per triangle, empty if no SurfaceMatData had one per triangle
+    pub materials: Vec<u32>,
+}
+
+/// `parser` then nothing left over
+fn exactly<'a, T>(data: &'a [u8], parser: impl FnMut(&'a [u8]) -> IResult<&'a [u8], T, ()>) -> Option<T> {
+    let mut parser = parser;
+    match parser(data) {
+        Ok(([], t)) => Some(t),
+        _ => None,
+    }
+}
+
+/// 0x06000007, num then num x, y, z floats
+fn verts(data: &[u8], endian: Endianness) -> Option<Vec<Vec3f>> {
+    exactly(data, |i| {
+        let (i, num) = u32(endian)(i)?;
+        count(|i| Vec3f::from_bytes(i, endian), num as usize)(i)
+    })
+}
+
+/// 0x03000007, the number of triangles then three u16 indices each
+fn tris(data: &[u8], endian: Endianness) -> Option<Vec<[u32; 3]>> {
+    let tris = exactly(data, |i| {
+        let (i, num) = u32(endian)(i)?;
+        count(tuple((u16(endian), u16(endian), u16(endian))), num as usize)(i)
+    })?;
+    Some(tris.into_iter().map(|(a, b, c)| [a as u32, b as u32, c as u32]).collect())
+}
+
+/// 0x12600107, num then a byte per triangle
+fn surface_materials(data: &[u8], endian: Endianness) -> Option<Vec<u32>> {
+    let materials = exactly(data, |i| {
+        let (i, num) = u32(endian)(i)?;
+        count(le_u8, num as usize)(i)
+    })?;
+    Some(materials.into_iter().map(|m| m as u32).collect())
+}
+
+impl RoomCollision {
+    /// from the children of a 0x0a000107, None with the reason printed if
+    /// the verts or triangles aren't there or don't fit
+    fn from_mesh(kids: &[u8], endian: Endianness) -> Option<RoomCollision> {
+        let kids: Vec<_> = children(kids, endian).collect();
+        let find = |id: u32| kids.iter().find(|c| c.0 == id).map(|c| c.1);
+
+        let Some(data) = find(VERTS) else {
+            println!(" BAD room_collision has no 0x06000007");
+            return None;
+        };
+        let Some(verts) = verts(data, endian) else {
+            println!(" BAD room_collision 0x06000007 isn't num then x, y, z floats, {:#x} bytes", data.len());
+            return None;
+        };
+        let Some(data) = find(TRIS) else {
+            println!(" BAD room_collision has no 0x03000007");
+            return None;
+        };
+        let Some(tris) = tris(data, endian) else {
+            println!(" BAD room_collision 0x03000007 isn't num then three u16s each, {:#x} bytes", data.len());
+            return None;
+        };
+        if let Some(t) = tris.iter().find(|t| t.iter().any(|i| *i as usize >= verts.len())) {
+            println!(" BAD room_collision triangle {:?} is past its {} verts", t, verts.len());
+            return None;
+        }
+
+        // SurfaceMatData turns up more than once, the first with one id
+        // per triangle wins
+        let mut materials = vec![];
+        for (_, _, saturated) in kids.iter().filter(|c| c.0 == SATURATED_DATA) {
+            for (_, data, _) in children(saturated, endian).filter(|c| c.0 == SURFACE_MAT_DATA) {
+                match surface_materials(data, endian) {
+                    Some(m) if m.len() == tris.len() => {
+                        if materials.is_empty() { materials = m; }
+                    }
+                    Some(m) => println!(" BAD SurfaceMatData has {} ids for {} triangles", m.len(), tris.len()),
+                    None => println!(" BAD SurfaceMatData isn't num then a byte each, {:#x} bytes", data.len()),
+                }
+            }
+        }
+        Some(RoomCollision {verts, tris, materials})
+    }
+
+    /// obj with a group per surface material, `surface_none` when there
+    /// weren't any
+    pub fn obj_write(&self, out_path: &Path) -> io::Result<()> {
+        let mut s = String::from("# room_collision\n");
+        for v in self.verts.iter() {
+            s += &format!("v {} {} {}\n", v.x, v.y, v.z);
+        }
+        let mut materials: Vec<Option<u32>> = match self.materials.is_empty() {
+            true => vec![None],
+            false => self.materials.iter().map(|m| Some(*m)).collect(),
+        };
+        materials.sort();
+        materials.dedup();
+        for material in materials {
+            s += &match material {
+                Some(m) => format!("g surface_{}\n", m),
+                None => "g surface_none\n".to_string(),
+            };
+            for (i, t) in self.tris.iter().enumerate() {
+                if material.is_none() || self.materials.get(i) == material.as_ref() {
+                    s += &format!("f {} {} {}\n", t[0] + 1, t[1] + 1, t[2] + 1);
+                }
+            }
+        }
+        OpenOptions::new().create(true).write(true).truncate(true).open(out_path)?.write_all(s.as_bytes())
+    }
+}
+
+/// every room_collision in the file, with what couldn't be decoded printed
+pub fn room_collisions(input: &[u8], v: &mut Vec<RoomCollision>) {
+    room_collisions_under(input, endian::detect(input), v);
+}
+
+fn room_collisions_under(input: &[u8], endian: Endianness, v: &mut Vec<RoomCollision>) {
+    for (id, _, kids) in children(input, endian) {
+        if id != ROOM_COLLISION {
+            room_collisions_under(kids, endian, v);
+            continue;
+        }
+        meshes(kids, endian, v);
+    }
+}
+
+/// every 0x0a000107 under a room_collision
+fn meshes(input: &[u8], endian: Endianness, v: &mut Vec<RoomCollision>) {
+    for (id, _, kids) in children(input, endian) {
+        if id != MESH {
+            meshes(kids, endian, v);
+            continue;
+        }
+        if let Some(room) = RoomCollision::from_mesh(kids, endian) {
+            println!(" GOOD room_collision {} verts {} tris {} materials",
+                room.verts.len(), room.tris.len(), room.materials.len());
+            v.push(room);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: u32, data: &[u8], kids: &[u8]) -> Vec<u8> {
+        let ds = 12 + data.len() as u32;
+        [&id.to_be_bytes()[..], &ds.to_le_bytes(), &(ds + kids.len() as u32).to_le_bytes(), data, kids].concat()
+    }
+    fn u32s(v: &[u32]) -> Vec<u8> { v.iter().flat_map(|u| u.to_le_bytes()).collect() }
+
+    fn square(materials: &[u8]) -> Vec<u8> {
+        let verts: Vec<u8> = [&u32s(&[4])[..], &[0f32, 0., 0., 1., 0., 0., 1., 1., 0., 0., 1., 0.].iter().flat_map(|f| f.to_le_bytes()).collect::<Vec<_>>()].concat();
+        let tris: Vec<u8> = [&u32s(&[2])[..], &[0u16, 1, 2, 0, 2, 3].iter().flat_map(|i| i.to_le_bytes()).collect::<Vec<_>>()].concat();
+        let surface = chunk(SURFACE_MAT_DATA, &[&u32s(&[materials.len() as u32])[..], materials].concat(), &[]);
+        [chunk(VERTS, &verts, &[]), chunk(TRIS, &tris, &[]), chunk(SATURATED_DATA, &[], &surface)].concat()
+    }
+
+    #[test]
+    fn mesh_with_materials() {
+        let room = RoomCollision::from_mesh(&square(&[3, 7]), Endianness::Little).unwrap();
+        assert_eq!(room.verts.len(), 4);
+        assert_eq!(room.tris, vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(room.materials, vec![3, 7]);
+    }
+
+    #[test]
+    fn materials_not_one_per_triangle() {
+        let room = RoomCollision::from_mesh(&square(&[3]), Endianness::Little).unwrap();
+        assert!(room.materials.is_empty());
+    }
+
+    #[test]
+    fn layouts_must_fit_exactly() {
+        let e = Endianness::Little;
+        assert_eq!(tris(&[&u32s(&[1])[..], &[0, 0, 1, 0, 2, 0]].concat(), e), Some(vec![[0, 1, 2]]));
+        // u32 indices aren't read as u16 ones
+        assert_eq!(tris(&u32s(&[1, 0, 1, 2]), e), None);
+        assert_eq!(verts(&u32s(&[1, 0, 0]), e), None);
+        assert_eq!(surface_materials(&u32s(&[1, 5]), e), None);
+        assert_eq!(surface_materials(&[1, 0, 0, 0, 5], e), Some(vec![5]));
+    }
+
+    #[test]
+    fn triangle_past_the_verts() {
+        let mut mesh = square(&[3, 7]);
+        // the last index of the second triangle
+        let at = mesh.windows(2).rposition(|w| w == [3, 0]).unwrap();
+        mesh[at] = 9;
+        assert_eq!(RoomCollision::from_mesh(&mesh, Endianness::Little), None);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    fmt::Debug,
+};
+use nom::{
+    IResult,
+    error::ParseError,
+    number::{Endianness, complete::{f32, u32}},
+    sequence::tuple,
+};
+
+use crate::chunk_ids::{SHADER, SHADER_TEXTURE, SHADER_INT, SHADER_FLOAT, SHADER_COLOR};
+use crate::chunks::children;
+use crate::endian;
+use crate::utils::{RGBA, four_cc, pstring};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Param {
+    /// the name of a texture, the same as the image's file name give or take
+    /// the extension
+    Texture(String, String),
+    Int(String, u32),
+    Float(String, f32),
+    Color(String, RGBA<u8>),
+}
+impl Param {
+    fn from_chunk<'a, E: ParseError<&'a [u8]> + Debug>(id: u32, input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Option<Param>, E> {
+        let (input, param) = four_cc(input)?;
+        Ok(match id {
+            SHADER_TEXTURE => { let (i, v) = pstring(input)?; (i, Some(Param::Texture(param, v))) }
+            SHADER_INT     => { let (i, v) = u32(endian)(input)?; (i, Some(Param::Int(param, v))) }
+            SHADER_FLOAT   => { let (i, v) = f32(endian)(input)?; (i, Some(Param::Float(param, v))) }
+            // an ARGB u32
+            SHADER_COLOR   => { let (i, v) = RGBA::from_bytes(input, endian)?; (i, Some(Param::Color(param, v.swap("BGRA")))) }
+            _ => (input, None),
+        })
+    }
+    pub fn name(&self) -> &str {
+        match self {
+            Param::Texture(p, _) | Param::Int(p, _) | Param::Float(p, _) | Param::Color(p, _) => p,
+        }
+    }
+}
+
+/// 0x00100100, what an X20 draws with, its name is the X20's name
+/// laid out like SHAR's shader: name, version, pddi shader, has
+/// translucency, vertex needs, vertex mask and the number of params, which
+/// are its children
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Shader {
+    pub name: String,
+    pub version: u32,
+    pub pddi_shader: String,
+    pub has_translucency: u32,
+    pub vertex_needs: u32,
+    pub vertex_mask: u32,
+    pub params: Vec<Param>,
+}
+impl Shader {
+    pub fn from_bytes<'a, E: ParseError<&'a [u8]> + Debug>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Shader, E> {
+        let (input, name) = pstring(input)?;
+        let (input, version) = u32(endian)(input)?;
+        let (input, pddi_shader) = pstring(input)?;
+        let (input, (has_translucency, vertex_needs, vertex_mask, _num_params)) =
+            tuple((u32(endian), u32(endian), u32(endian), u32(endian)))(input)?;
+        Ok((input, Shader {name, version, pddi_shader, has_translucency, vertex_needs, vertex_mask, params: vec![]}))
+    }
+
+    pub fn texture(&self) -> Option<&str> {
+        self.params.iter().find_map(|p| match p {
+            Param::Texture(_, t) => Some(t.as_str()),
+            _ => None,
+        })
+    }
+    pub fn color(&self, param: &str) -> Option<&RGBA<u8>> {
+        self.params.iter().find_map(|p| match p {
+            Param::Color(name, c) if name == param => Some(c),
+            _ => None,
+        })
+    }
+    pub fn mtl_name(&self) -> String { mtl_name(&self.name) }
+}
+
+/// mtl names can't have spaces, X20 names still have their nulls
+pub fn mtl_name(name: &str) -> String { name.replace('\0', "").replace(' ', "_") }
+
+/// every shader in the file with its params
+pub fn shaders(input: &[u8], v: &mut Vec<Shader>) {
+    shaders_under(input, endian::detect(input), v);
+}
+
+fn shaders_under(input: &[u8], endian: Endianness, v: &mut Vec<Shader>) {
+    for (id, data, kids) in children(input, endian) {
+        if id != SHADER {
+            shaders_under(kids, endian, v);
+            continue;
+        }
+
+        match Shader::from_bytes::<()>(data, endian) {
+            Ok((_, mut shader)) => {
+                for (id, data, _) in children(kids, endian) {
+                    match Param::from_chunk::<()>(id, data, endian) {
+                        Ok((_, Some(param))) => shader.params.push(param),
+                        Ok((_, None)) => (),
+                        Err(_) => println!(" BAD {} param {:08x}", shader.name, id),
+                    }
+                }
+                v.push(shader);
+            }
+            Err(_) => println!(" BAD shader"),
+        }
+    }
+}
+
+/// the shaders, and the png each texture was written to, for exports to
+/// point materials at, names can repeat between the p3ds in a level so
+/// both are kept with the p3d they came from
+#[derive(Debug, Default)]
+pub struct Materials {
+    /// (p3d, shader)
+    pub shaders: Vec<(String, Shader)>,
+    /// (p3d, texture name, png path relative to the export)
+    pub textures: Vec<(String, String, String)>,
+}
+impl Materials {
+    /// what `shaders` and `img::export` found in `p3d`
+    pub fn add(&mut self, p3d: &str, shaders: Vec<Shader>, textures: Vec<(String, String)>) {
+        self.shaders.extend(shaders.into_iter().map(|s| (p3d.to_string(), s)));
+        self.textures.extend(textures.into_iter().map(|(name, png)| (p3d.to_string(), name, png)));
+    }
+
+    /// the shader called `name`, from `p3d` if it has one and otherwise
+    /// the first other file's, with the p3d it's from
+    pub fn shader(&self, p3d: &str, name: &str) -> Option<(&str, &Shader)> {
+        let name = name.replace("\x00", "");
+        let found = |own: bool| self.shaders.iter()
+            .find(|(file, s)| (file == p3d) == own && s.name.eq_ignore_ascii_case(&name));
+        found(true).or_else(|| found(false)).map(|(file, s)| (file.as_str(), s))
+    }
+    /// the same for a texture, which is named with or without the image's
+    /// extension
+    pub fn png(&self, p3d: &str, texture: &str) -> Option<&str> {
+        let stem = |s: &str| s.rsplit_once('.').map_or(s, |(stem, _)| stem).to_lowercase();
+        let texture = stem(texture);
+        let found = |own: bool| self.textures.iter()
+            .find(|(file, name, _)| (file == p3d) == own && stem(name) == texture);
+        found(true).or_else(|| found(false)).map(|(_, _, png)| png.as_str())
+    }
+
+    /// newmtl per shader, with the diffuse colour and texture when it has them
+    pub fn mtl(&self) -> String {
+        let mut s = String::new();
+        for (p3d, shader) in self.shaders.iter() {
+            s += &format!("newmtl {}\n", shader.mtl_name());
+            let c = shader.color("DIFF").map(|c| c.float().to_one()).unwrap_or(RGBA {r: 1., g: 1., b: 1., a: 1.});
+            s += &format!("Kd {} {} {}\n", c.r, c.g, c.b);
+            if shader.has_translucency != 0 { s += &format!("d {}\n", c.a); }
+            if let Some(png) = shader.texture().and_then(|t| self.png(p3d, t)) {
+                s += &format!("map_Kd {}\n", png);
+            }
+            s += "\n";
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shader(name: &str, texture: &str) -> Shader {
+        Shader {name: name.to_string(), params: vec![Param::Texture("TEX".to_string(), texture.to_string())], ..Default::default()}
+    }
+    fn materials() -> Materials {
+        let mut m = Materials::default();
+        m.add("a.p3d", vec![shader("wall", "brick.tga")], vec![("brick.tga".to_string(), "a/brick.png".to_string())]);
+        m.add("b.p3d", vec![shader("wall", "brick")], vec![("brick.tga".to_string(), "b/brick.png".to_string())]);
+        m
+    }
+
+    #[test]
+    fn own_p3d_first() {
+        let m = materials();
+        assert_eq!(m.shader("b.p3d", "WALL\0").map(|(p3d, _)| p3d), Some("b.p3d"));
+        assert_eq!(m.png("b.p3d", "brick"), Some("b/brick.png"));
+        assert_eq!(m.png("a.p3d", "brick.png"), Some("a/brick.png"));
+    }
+
+    #[test]
+    fn other_p3ds_after() {
+        let m = materials();
+        assert_eq!(m.shader("c.p3d", "wall").map(|(p3d, _)| p3d), Some("a.p3d"));
+        assert_eq!(m.png("c.p3d", "brick"), Some("a/brick.png"));
+        assert_eq!(m.shader("a.p3d", "floor"), None);
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    fmt::Debug,
+    io,
+    path::Path,
+};
+use nom::{
+    IResult,
+    error::ParseError,
+    multi::count,
+    number::{Endianness, complete::{f32, i16, u16, u32}},
+    sequence::tuple,
+};
+use serde_json::{json, Value};
+
+use crate::chunk_ids::{
+    SKIN, SKELETON, SKELETON_JOINT, ANIMATION, ANIMATION_GROUP, X20_00_01_00 as X20_ID,
+    VECTOR_1DOF_CHANNEL, VECTOR_2DOF_CHANNEL, VECTOR_3DOF_CHANNEL,
+    QUATERNION_CHANNEL, COMPRESSED_QUATERNION_CHANNEL,
+};
+use crate::chunks::{FromBytes, children};
+use crate::col::X20;
+use crate::endian;
+use crate::gltf::Gltf;
+use crate::utils::{four_cc, pstring};
+
+/// 0x01450000, the rest pose is relative to the parent, a root joint is its
+/// own parent
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Joint {
+    pub name: String,
+    pub parent: u32,
+    pub dof: u32,
+    pub free_axis: u32,
+    pub primary_axis: u32,
+    pub secondary_axis: u32,
+    pub twist_axis: u32,
+    /// row major with the translation in the last row, which is glTF's
+    /// column major order as is
+    pub rest_pose: [f32; 16],
+}
+impl FromBytes for Joint {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Joint, E>
+        where
+            E: ParseError<&'a [u8]> + Debug, {
+        let (input, name) = pstring(input)?;
+        let (input, (parent, dof, free_axis, primary_axis, secondary_axis, twist_axis)) =
+            tuple((u32(endian), u32(endian), u32(endian), u32(endian), u32(endian), u32(endian)))(input)?;
+        let (input, rest_pose) = count(f32(endian), 16)(input)?;
+        let rest_pose = rest_pose.try_into().unwrap();
+        Ok((input, Joint {name, parent, dof, free_axis, primary_axis, secondary_axis, twist_axis, rest_pose}))
+    }
+}
+
+/// 0x00450000, name, version and number of joints, the joints are its
+/// children
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Skeleton {
+    pub name: String,
+    pub version: u32,
+    pub joints: Vec<Joint>,
+}
+impl Skeleton {
+    /// model space rest pose of every joint
+    pub fn world(&self) -> Vec<[f32; 16]> {
+        let mut world: Vec<[f32; 16]> = vec![];
+        for (i, joint) in self.joints.iter().enumerate() {
+            let m = match joint.parent as usize {
+                p if p < i => mul(&world[p], &joint.rest_pose),
+                _ => joint.rest_pose,
+            };
+            world.push(m);
+        }
+        world
+    }
+}
+
+/// 0x01000100, a mesh whose prim groups carry weights for the skeleton it
+/// names
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Skin {
+    pub name: String,
+    pub version: u32,
+    pub skeleton: String,
+    pub x20s: Vec<X20>,
+}
+
+/// the keys of one channel, 1 and 2 dof vectors are filled out to 3 from
+/// the channel's constants
+#[derive(Debug, PartialEq, Clone)]
+pub enum Keys {
+    Vector(Vec<[f32; 3]>),
+    /// x, y, z, w like glTF, the file has w first
+    Quaternion(Vec<[f32; 4]>),
+}
+#[derive(Debug, PartialEq, Clone)]
+pub struct Channel {
+    /// "TRAN", "ROT ", ...
+    pub param: String,
+    pub frames: Vec<u16>,
+    pub keys: Keys,
+}
+impl Channel {
+    fn from_chunk<'a, E: ParseError<&'a [u8]> + Debug>(id: u32, input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], Option<Channel>, E> {
+        let (input, (_version, param)) = tuple((u32(endian), four_cc))(input)?;
+        // 1 and 2 dof say which of x, y and z the values are for
+        let (input, mapping_constants) = match id {
+            VECTOR_1DOF_CHANNEL | VECTOR_2DOF_CHANNEL => {
+                let (input, (mapping, x, y, z)) = tuple((u16(endian), f32(endian), f32(endian), f32(endian)))(input)?;
+                (input, Some((mapping, [x, y, z])))
+            }
+            _ => (input, None),
+        };
+        let (input, num) = u32(endian)(input)?;
+        let (input, frames) = count(u16(endian), num as usize)(input)?;
+        let num = num as usize;
+        let (input, keys) = match (id, mapping_constants) {
+            (VECTOR_1DOF_CHANNEL, Some((mapping, constants))) => {
+                let (input, values) = count(f32(endian), num)(input)?;
+                (input, Keys::Vector(values.into_iter().map(|f| {
+                    let mut v = constants;
+                    v[(mapping as usize).min(2)] = f;
+                    v
+                }).collect()))
+            }
+            (VECTOR_2DOF_CHANNEL, Some((mapping, constants))) => {
+                let (input, values) = count(tuple((f32(endian), f32(endian))), num)(input)?;
+                // mapping is the one that stays constant
+                let (a, b) = match mapping { 0 => (1, 2), 1 => (0, 2), _ => (0, 1) };
+                (input, Keys::Vector(values.into_iter().map(|(f1, f2)| {
+                    let mut v = constants;
+                    (v[a], v[b]) = (f1, f2);
+                    v
+                }).collect()))
+            }
+            (VECTOR_3DOF_CHANNEL, _) => {
+                let (input, values) = count(tuple((f32(endian), f32(endian), f32(endian))), num)(input)?;
+                (input, Keys::Vector(values.into_iter().map(|(x, y, z)| [x, y, z]).collect()))
+            }
+            (QUATERNION_CHANNEL, _) => {
+                let (input, values) = count(tuple((f32(endian), f32(endian), f32(endian), f32(endian))), num)(input)?;
+                (input, Keys::Quaternion(values.into_iter().map(|(w, x, y, z)| [x, y, z, w]).collect()))
+            }
+            (COMPRESSED_QUATERNION_CHANNEL, _) => {
+                let q = |i: i16| i as f32 / 32767.;
+                let (input, values) = count(tuple((i16(endian), i16(endian), i16(endian), i16(endian))), num)(input)?;
+                (input, Keys::Quaternion(values.into_iter().map(|(w, x, y, z)| [q(x), q(y), q(z), q(w)]).collect()))
+            }
+            _ => return Ok((input, None)),
+        };
+        Ok((input, Some(Channel {param, frames, keys})))
+    }
+}
+
+/// 0x01101200, the channels for one joint, matched by name
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct AnimationGroup {
+    pub name: String,
+    pub group_id: u32,
+    pub channels: Vec<Channel>,
+}
+
+/// 0x00101200, version, name, type, number of frames, frame rate and
+/// cyclic, the groups are under a 0x02101200 group list
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Animation {
+    pub name: String,
+    pub kind: String,
+    pub num_frames: f32,
+    pub frame_rate: f32,
+    pub cyclic: u32,
+    pub groups: Vec<AnimationGroup>,
+}
+
+/// every skeleton, skin and animation in one or more files
+#[derive(Debug, Default)]
+pub struct Character {
+    pub skeletons: Vec<Skeleton>,
+    pub skins: Vec<Skin>,
+    pub animations: Vec<Animation>,
+}
+impl Character {
+    /// adds what's in the file `input`
+    pub fn read(&mut self, input: &[u8]) {
+        self.read_under(input, endian::detect(input));
+    }
+
+    fn read_under(&mut self, input: &[u8], endian: Endianness) {
+        for (id, data, kids) in children(input, endian) {
+            match id {
+                SKELETON => match tuple((pstring::<()>, u32(endian)))(data) {
+                    Ok((_, (name, version))) => {
+                        let joints: Vec<Joint> = children(kids, endian)
+                            .filter(|c| c.0 == SKELETON_JOINT)
+                            .filter_map(|(_, data, _)| Joint::from_bytes::<()>(data, endian).ok().map(|(_, j)| j))
+                            .collect();
+                        println!(" GOOD skeleton {} {} joints", name, joints.len());
+                        self.skeletons.push(Skeleton {name, version, joints});
+                    }
+                    Err(_) => println!(" BAD skeleton"),
+                },
+                SKIN => match tuple((pstring::<()>, u32(endian), pstring))(data) {
+                    Ok((_, (name, version, skeleton))) => {
+                        let mut x20s = vec![];
+                        let mut chunks = children(kids, endian);
+                        loop {
+                            let input = chunks.rest();
+                            let Some((id, _, _)) = chunks.next() else { break };
+                            if id != X20_ID { continue; }
+                            // a prim group reads its own children
+                            let cs = input.len() - chunks.rest().len();
+                            match X20::from_bytes::<()>(&input[12..cs], endian) {
+                                Ok((_, x20)) => x20s.push(x20),
+                                Err(_) => println!(" BAD skin {} prim group", name),
+                            }
+                        }
+                        println!(" GOOD skin {} on {} {} prim groups", name, skeleton, x20s.len());
+                        self.skins.push(Skin {name, version, skeleton, x20s});
+                    }
+                    Err(_) => println!(" BAD skin"),
+                },
+                ANIMATION => match tuple((u32::<&[u8], ()>(endian), pstring, four_cc, f32(endian), f32(endian), u32(endian)))(data) {
+                    Ok((_, (_version, name, kind, num_frames, frame_rate, cyclic))) => {
+                        let mut groups = vec![];
+                        animation_groups(kids, endian, &mut groups);
+                        println!(" GOOD animation {} {} {} frames {} groups", name, kind, num_frames, groups.len());
+                        self.animations.push(Animation {name, kind, num_frames, frame_rate, cyclic, groups});
+                    }
+                    Err(_) => println!(" BAD animation"),
+                },
+                _ => self.read_under(kids, endian),
+            }
+        }
+    }
+
+    /// a node per joint, a mesh per skin and an animation per animation,
+    /// channels go to the joint their group is named after
+    pub fn export(&self, out_path: &Path) -> io::Result<()> {
+        let mut gltf = Gltf::new();
+        // skeleton name -> (joint nodes, skin)
+        let mut rigs: Vec<(&str, Vec<usize>, usize)> = vec![];
+        for skeleton in self.skeletons.iter() {
+            let mut nodes: Vec<usize> = vec![];
+            for (i, joint) in skeleton.joints.iter().enumerate() {
+                let (translation, rotation, scale) = decompose(&joint.rest_pose);
+                let is_root = joint.parent as usize >= i;
+                let node = gltf.node(json!({
+                    "name": joint.name, "translation": translation, "rotation": rotation, "scale": scale,
+                    "extras": {"dof": joint.dof, "free_axis": joint.free_axis, "primary_axis": joint.primary_axis,
+                               "secondary_axis": joint.secondary_axis, "twist_axis": joint.twist_axis},
+                }), is_root);
+                if !is_root { gltf.add_child(nodes[joint.parent as usize], node); }
+                nodes.push(node);
+            }
+            let inverse_bind: Vec<[f32; 16]> = skeleton.world().iter().map(inverse).collect();
+            let inverse_bind = gltf.mat4s(&inverse_bind);
+            let skin = gltf.skin(json!({
+                "name": skeleton.name, "joints": nodes, "inverseBindMatrices": inverse_bind,
+                "skeleton": nodes.first(),
+            }));
+            rigs.push((&skeleton.name, nodes, skin));
+        }
+
+        for skin in self.skins.iter() {
+            let primitives: Vec<Value> = skin.x20s.iter().flat_map(|x20| gltf.x20(x20)).collect();
+            if primitives.is_empty() { continue; }
+            let mesh = gltf.mesh(&skin.name, primitives);
+            let mut node = json!({"name": skin.name, "mesh": mesh});
+            // left unbound rather than put on some other skeleton
+            match rigs.iter().find(|r| r.0.eq_ignore_ascii_case(&skin.skeleton)) {
+                Some((_, _, i)) => node["skin"] = json!(i),
+                None => println!(" BAD skin {} is bound to skeleton {} which isn't in {:?}, left unbound",
+                    skin.name, skin.skeleton, rigs.iter().map(|r| r.0).collect::<Vec<_>>()),
+            }
+            gltf.node(node, true);
+        }
+
+        for animation in self.animations.iter() {
+            let rate = if animation.frame_rate > 0. { animation.frame_rate } else { 30. };
+            let (mut samplers, mut channels) = (vec![], vec![]);
+            for group in animation.groups.iter() {
+                let joint = self.skeletons.iter().zip(rigs.iter())
+                    .find_map(|(s, r)| s.joints.iter().position(|j| j.name.eq_ignore_ascii_case(&group.name)).map(|i| r.1[i]));
+                let Some(joint) = joint else {
+                    println!(" BAD animation {} group {} has no joint", animation.name, group.name);
+                    continue;
+                };
+                for channel in group.channels.iter() {
+                    // four ccs are padded with spaces, "ROT "
+                    let path = match (channel.param.trim_end(), &channel.keys) {
+                        ("TRAN", Keys::Vector(_)) => "translation",
+                        ("ROT", Keys::Quaternion(_)) => "rotation",
+                        _ => continue,
+                    };
+                    let times: Vec<f32> = channel.frames.iter().map(|f| *f as f32 / rate).collect();
+                    let input = gltf.scalars(&times);
+                    let output = match &channel.keys {
+                        Keys::Vector(v) => gltf.floats(v),
+                        Keys::Quaternion(q) => gltf.floats(&q.iter().map(|q| unit_quat(*q)).collect::<Vec<_>>()),
+                    };
+                    samplers.push(json!({"input": input, "output": output, "interpolation": "LINEAR"}));
+                    channels.push(json!({"sampler": samplers.len() - 1, "target": {"node": joint, "path": path}}));
+                }
+            }
+            if channels.is_empty() { continue; }
+            gltf.animation(json!({
+                "name": animation.name, "samplers": samplers, "channels": channels,
+                "extras": {"type": animation.kind, "num_frames": animation.num_frames, "frame_rate": animation.frame_rate, "cyclic": animation.cyclic},
+            }));
+        }
+
+        println!("{} skeletons, {} skins, {} animations", self.skeletons.len(), self.skins.len(), self.animations.len());
+        gltf.write(out_path)
+    }
+}
+
+fn animation_groups(input: &[u8], endian: Endianness, v: &mut Vec<AnimationGroup>) {
+    for (id, data, kids) in children(input, endian) {
+        if id != ANIMATION_GROUP {
+            animation_groups(kids, endian, v);
+            continue;
+        }
+        let Ok((_, (_version, name, group_id))) = tuple((u32::<&[u8], ()>(endian), pstring, u32(endian)))(data) else {
+            println!(" BAD animation group");
+            continue;
+        };
+        let mut channels = vec![];
+        for (id, data, _) in children(kids, endian) {
+            match Channel::from_chunk::<()>(id, data, endian) {
+                Ok((_, Some(channel))) => channels.push(channel),
+                Ok((_, None)) => (),
+                Err(_) => println!(" BAD {} channel {:08x}", name, id),
+            }
+        }
+        v.push(AnimationGroup {name, group_id, channels});
+    }
+}
+
+/// column major, `a` after `b`
+fn mul(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+    let mut m = [0.; 16];
+    for c in 0..4 {
+        for r in 0..4 {
+            m[c * 4 + r] = (0..4).map(|k| a[k * 4 + r] * b[c * 4 + k]).sum();
+        }
+    }
+    m
+}
+
+/// of an affine matrix, the last row's taken to be 0 0 0 1
+fn inverse(m: &[f32; 16]) -> [f32; 16] {
+    let a = |r: usize, c: usize| m[c * 4 + r];
+    let det = a(0, 0) * (a(1, 1) * a(2, 2) - a(1, 2) * a(2, 1))
+        - a(0, 1) * (a(1, 0) * a(2, 2) - a(1, 2) * a(2, 0))
+        + a(0, 2) * (a(1, 0) * a(2, 1) - a(1, 1) * a(2, 0));
+    if det == 0. { return IDENTITY; }
+    let mut inv = [0.; 16];
+    for r in 0..3 {
+        for c in 0..3 {
+            // cofactor of (c, r), transposed
+            let (r1, r2) = ([1, 0, 0][c], [2, 2, 1][c]);
+            let (c1, c2) = ([1, 0, 0][r], [2, 2, 1][r]);
+            let minor = a(r1, c1) * a(r2, c2) - a(r1, c2) * a(r2, c1);
+            let sign = if (r + c) % 2 == 0 { 1. } else { -1. };
+            inv[c * 4 + r] = sign * minor / det;
+        }
+    }
+    for r in 0..3 {
+        inv[12 + r] = -(0..3).map(|k| inv[k * 4 + r] * m[12 + k]).sum::<f32>();
+    }
+    inv[15] = 1.;
+    inv
+}
+
+const IDENTITY: [f32; 16] = [1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.];
+
+/// translation, rotation (x, y, z, w) and scale
+fn decompose(m: &[f32; 16]) -> ([f32; 3], [f32; 4], [f32; 3]) {
+    let translation = [m[12], m[13], m[14]];
+    let len = |c: usize| (m[c * 4] * m[c * 4] + m[c * 4 + 1] * m[c * 4 + 1] + m[c * 4 + 2] * m[c * 4 + 2]).sqrt();
+    let scale = [len(0), len(1), len(2)];
+    let a = |r: usize, c: usize| if scale[c] == 0. { 0. } else { m[c * 4 + r] / scale[c] };
+
+    let trace = a(0, 0) + a(1, 1) + a(2, 2);
+    let rotation = if trace > 0. {
+        let s = (trace + 1.).sqrt() * 2.;
+        [(a(2, 1) - a(1, 2)) / s, (a(0, 2) - a(2, 0)) / s, (a(1, 0) - a(0, 1)) / s, s / 4.]
+    } else if a(0, 0) > a(1, 1) && a(0, 0) > a(2, 2) {
+        let s = (1. + a(0, 0) - a(1, 1) - a(2, 2)).sqrt() * 2.;
+        [s / 4., (a(0, 1) + a(1, 0)) / s, (a(0, 2) + a(2, 0)) / s, (a(2, 1) - a(1, 2)) / s]
+    } else if a(1, 1) > a(2, 2) {
+        let s = (1. + a(1, 1) - a(0, 0) - a(2, 2)).sqrt() * 2.;
+        [(a(0, 1) + a(1, 0)) / s, s / 4., (a(1, 2) + a(2, 1)) / s, (a(0, 2) - a(2, 0)) / s]
+    } else {
+        let s = (1. + a(2, 2) - a(0, 0) - a(1, 1)).sqrt() * 2.;
+        [(a(0, 2) + a(2, 0)) / s, (a(1, 2) + a(2, 1)) / s, s / 4., (a(1, 0) - a(0, 1)) / s]
+    };
+    (translation, unit_quat(rotation), scale)
+}
+
+/// glTF wants unit rotations, an all zero one is no rotation
+fn unit_quat([x, y, z, w]: [f32; 4]) -> [f32; 4] {
+    let len = (x * x + y * y + z * z + w * w).sqrt();
+    if len == 0. { [0., 0., 0., 1.] } else { [x / len, y / len, z / len, w / len] }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{read_to_string, remove_file};
+    use crate::chunk_ids::ANIMATION_GROUP_LIST;
+
+    /// a little endian chunk, the id as the tree prints it
+    fn chunk(id: u32, data: &[u8], kids: &[u8]) -> Vec<u8> {
+        let ds = 12 + data.len() as u32;
+        [&id.to_be_bytes()[..], &ds.to_le_bytes(), &(ds + kids.len() as u32).to_le_bytes(), data, kids].concat()
+    }
+    fn le(v: &[u32]) -> Vec<u8> { v.iter().flat_map(|u| u.to_le_bytes()).collect() }
+    fn lef(v: &[f32]) -> Vec<u8> { v.iter().flat_map(|f| f.to_le_bytes()).collect() }
+
+    /// scale 2, a quarter turn about z, then moved to 1, 2, 3
+    const TRS: [f32; 16] = [0., 2., 0., 0., -2., 0., 0., 0., 0., 0., 2., 0., 1., 2., 3., 1.];
+    const HALF: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+    fn close(a: &[f32], b: &[f32]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn mul_and_inverse() {
+        assert_eq!(mul(&IDENTITY, &TRS), TRS);
+        assert_eq!(mul(&TRS, &IDENTITY), TRS);
+        assert!(close(&mul(&inverse(&TRS), &TRS), &IDENTITY));
+        assert!(close(&mul(&TRS, &inverse(&TRS)), &IDENTITY));
+        // a point 1 along x is turned onto y, scaled and moved
+        let point = mul(&TRS, &[0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 1., 0., 0., 1.]);
+        assert_eq!(&point[12..], &[1., 4., 3., 1.]);
+        assert_eq!(inverse(&[0.; 16]), IDENTITY);
+    }
+
+    #[test]
+    fn decompose_trs() {
+        let (t, r, s) = decompose(&TRS);
+        assert_eq!(t, [1., 2., 3.]);
+        assert!(close(&r, &[0., 0., HALF, HALF]));
+        assert!(close(&s, &[2., 2., 2.]));
+        assert_eq!(decompose(&IDENTITY), ([0.; 3], [0., 0., 0., 1.], [1.; 3]));
+        assert_eq!(unit_quat([0.; 4]), [0., 0., 0., 1.]);
+    }
+
+    #[test]
+    fn channels() {
+        let one_dof = [&le(&[0])[..], b"TRAN", &1u16.to_le_bytes(), &lef(&[5., 6., 7.]),
+            &le(&[2]), &[0, 0, 10, 0], &lef(&[1., 2.])].concat();
+        let (_, c) = Channel::from_chunk::<()>(VECTOR_1DOF_CHANNEL, &one_dof, Endianness::Little).unwrap();
+        assert_eq!(c, Some(Channel {param: "TRAN".into(), frames: vec![0, 10], keys: Keys::Vector(vec![[5., 1., 7.], [5., 2., 7.]])}));
+
+        // mapping 0 keeps x
+        let two_dof = [&le(&[0])[..], b"TRAN", &0u16.to_le_bytes(), &lef(&[5., 6., 7.]),
+            &le(&[1]), &[3, 0], &lef(&[1., 2.])].concat();
+        let (_, c) = Channel::from_chunk::<()>(VECTOR_2DOF_CHANNEL, &two_dof, Endianness::Little).unwrap();
+        assert_eq!(c.unwrap().keys, Keys::Vector(vec![[5., 1., 2.]]));
+
+        // w first in the file
+        let quat = [&le(&[0])[..], b"ROT ", &le(&[1]), &[0, 0], &lef(&[1., 2., 3., 4.])].concat();
+        let (_, c) = Channel::from_chunk::<()>(QUATERNION_CHANNEL, &quat, Endianness::Little).unwrap();
+        assert_eq!(c, Some(Channel {param: "ROT ".into(), frames: vec![0], keys: Keys::Quaternion(vec![[2., 3., 4., 1.]])}));
+
+        let compressed = [&le(&[0])[..], b"ROT ", &le(&[1]), &[0, 0],
+            &[32767i16, 0, 0, -32767].iter().flat_map(|i| i.to_le_bytes()).collect::<Vec<_>>()].concat();
+        let (_, c) = Channel::from_chunk::<()>(COMPRESSED_QUATERNION_CHANNEL, &compressed, Endianness::Little).unwrap();
+        assert_eq!(c.unwrap().keys, Keys::Quaternion(vec![[0., 0., -1., 1.]]));
+
+        assert_eq!(Channel::from_chunk::<()>(SKELETON, &quat, Endianness::Little).unwrap().1, None);
+        assert!(Channel::from_chunk::<()>(QUATERNION_CHANNEL, &quat[..20], Endianness::Little).is_err());
+    }
+
+    #[test]
+    fn rot_channels_are_exported() {
+        let joint = [&[4][..], b"root", &le(&[0; 6]), &lef(&IDENTITY)].concat();
+        let skeleton = chunk(SKELETON, &[&[4][..], b"skel", &le(&[0])].concat(), &chunk(SKELETON_JOINT, &joint, &[]));
+        let rot = chunk(QUATERNION_CHANNEL, &[&le(&[0])[..], b"ROT ", &le(&[2]), &[0, 0, 30, 0],
+            &lef(&[1., 0., 0., 0., HALF, 0., 0., HALF])].concat(), &[]);
+        let tran = chunk(VECTOR_3DOF_CHANNEL, &[&le(&[0])[..], b"TRAN", &le(&[1]), &[0, 0], &lef(&[1., 2., 3.])].concat(), &[]);
+        let group = chunk(ANIMATION_GROUP, &[&le(&[0])[..], &[4], b"root", &le(&[0])].concat(), &[rot, tran].concat());
+        let animation = chunk(ANIMATION, &[&le(&[0])[..], &[4], b"spin", b"PANI", &lef(&[30., 30.]), &le(&[1])].concat(),
+            &chunk(ANIMATION_GROUP_LIST, &le(&[1]), &group));
+
+        let mut character = Character::default();
+        character.read(&[skeleton, animation].concat());
+        assert_eq!(character.animations[0].groups[0].channels[0].param, "ROT ");
+
+        let out = std::env::temp_dir().join(format!("p3d_skin_{}.gltf", std::process::id()));
+        character.export(&out).unwrap();
+        let gltf: Value = serde_json::from_str(&read_to_string(&out).unwrap()).unwrap();
+        _ = remove_file(&out);
+        _ = remove_file(out.with_extension("bin"));
+        let paths: Vec<&str> = gltf["animations"][0]["channels"].as_array().unwrap().iter()
+            .map(|c| c["target"]["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, ["rotation", "translation"]);
+    }
+}
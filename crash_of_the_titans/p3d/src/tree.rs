@@ -0,0 +1,36 @@
+use nom::number::Endianness;
+
+use crate::chunk_ids::name;
+use crate::chunks::{Header, children};
+
+const HEADER_SIZE: usize = 12;
+
+/// one line per chunk, children indented 4 more than their parent, the
+/// same layout as stuff/l1_e1_r1/l1e1r1_chunk_tree.txt
+/// `ids_only` leaves out the offset, sizes and name to diff against it
+pub fn tree(input: &[u8], endian: Endianness, offset: usize, depth: usize, ids_only: bool, lines: &mut Vec<String>) {
+    let indent = "    ".repeat(depth);
+    let mut chunks = children(input, endian);
+    let mut pos = 0;
+    for (id, data, kids) in chunks.by_ref() {
+        let (ds, cs) = (HEADER_SIZE + data.len(), HEADER_SIZE + data.len() + kids.len());
+        if ids_only {
+            lines.push(format!("{}{:08x}", indent, id));
+        } else {
+            lines.push(format!(
+                "{}{:08x} {:#010x} ds={:#x} cs={:#x} {}",
+                indent, id, offset + pos, ds, cs, name(id).unwrap_or("")
+            ).trim_end().to_string());
+        }
+        tree(kids, endian, offset + pos + ds, depth + 1, ids_only, lines);
+        pos += cs;
+    }
+
+    let rest = chunks.rest();
+    if rest.is_empty() { return; }
+    match Header::from_bytes::<()>(rest, endian) {
+        Ok((_, Header {id, ds, cs})) =>
+            lines.push(format!("{}BAD {:08x} {:#010x} ds={:#x} cs={:#x}", indent, id, offset + pos, ds, cs)),
+        Err(_) => lines.push(format!("{}BAD {:#010x} {:#x} bytes left over", indent, offset + pos, rest.len())),
+    }
+}
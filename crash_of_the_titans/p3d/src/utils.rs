@@ -4,20 +4,34 @@ use std::{
 };
 use nom::{
     IResult,
+    bytes::complete::take,
     error::ParseError,
-    number::complete::{le_f32, le_u8},
+    multi::length_data,
+    number::{Endianness, complete::{f32, le_u8, u32}},
     sequence::tuple,
 };
 
+/// a byte long then that many chars, without its nulls
+pub fn pstring<'a, E: ParseError<&'a [u8]> + Debug>(input: &'a [u8]) -> IResult<&'a [u8], String, E> {
+    let (input, s) = length_data(le_u8)(input)?;
+    Ok((input, String::from_utf8_lossy(s).replace("\x00", "")))
+}
+
+/// the four chars naming a parameter, "TEX", "DIFF", "SHIN", ...
+pub fn four_cc<'a, E: ParseError<&'a [u8]> + Debug>(input: &'a [u8]) -> IResult<&'a [u8], String, E> {
+    let (input, cc) = take(4usize)(input)?;
+    Ok((input, String::from_utf8_lossy(cc).replace("\x00", "")))
+}
+
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Vec3f {pub x: f32, pub y: f32, pub z: f32}
 impl Vec3f {
     // pub fn new() -> Vec3f { Default::default() }
     pub fn from_bytes<'a, E: ParseError<&'a [u8]> + Debug>(
-        input: &'a [u8]
+        input: &'a [u8], endian: Endianness
     ) -> IResult<&'a [u8], Vec3f, E> {
         let (input, (x, y, z)) =
-            tuple((le_f32, le_f32, le_f32))(input)?;
+            tuple((f32(endian), f32(endian), f32(endian)))(input)?;
         Ok((input, Vec3f {x, y, z}))
     }
 }
@@ -36,11 +50,12 @@ impl<T: Clone> RGBA<T> {
 }
 impl RGBA<u8> {
     pub fn new() -> RGBA<u8> { Default::default() }
-    pub fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], RGBA<u8>, E>
+    pub fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], RGBA<u8>, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, (r, g, b, a)) =
-            tuple((le_u8, le_u8, le_u8, le_u8))(input)?;
+        // a u32 in the file's byte order, so console colours come out the same
+        let (input, rgba) = u32(endian)(input)?;
+        let [r, g, b, a] = rgba.to_le_bytes();
         Ok((input, RGBA {r, g, b, a}))
     }
     pub fn float(&self) -> RGBA<f32> {
@@ -52,3 +67,19 @@ impl RGBA<f32> {
         RGBA {r: self.r/255., g: self.g/255., b: self.b/255., a: self.a/255.}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pstring_drops_nulls() {
+        assert_eq!(pstring::<()>(b"\x04ab\0\0rest"), Ok((&b"rest"[..], "ab".to_string())));
+        assert!(pstring::<()>(b"\x04ab").is_err());
+    }
+
+    #[test]
+    fn four_cc_is_four_bytes() {
+        assert_eq!(four_cc::<()>(b"TEX\0\x01"), Ok((&b"\x01"[..], "TEX".to_string())));
+    }
+}
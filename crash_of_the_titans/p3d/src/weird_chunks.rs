@@ -3,54 +3,40 @@ use nom::{
     IResult,
     error::ParseError,
     multi::count,
-    number::complete::{le_f32, le_u8, le_u32},
+    number::{Endianness, complete::{f32, le_u8, u32}},
     sequence::tuple,
 };
 
 use crate::utils::Vec3f;
 use crate::chunks::FromBytes;
 
+/// 0x03000100, min corner then max corner, what was f1..f6
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct X03 {
-    f1: f32,
-    f2: f32,
-    f3: f32,
-    f4: f32,
-    f5: f32,
-    f6: f32,
-}
-impl FromBytes for X03 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X03, E>
+pub struct BBox {
+    pub min: Vec3f,
+    pub max: Vec3f,
+}
+impl FromBytes for BBox {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], BBox, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, (f1, f2, f3, f4, f5, f6)) = tuple((
-            le_f32::<&'a [u8], E>, le_f32, le_f32, le_f32, le_f32, le_f32
-        ))(input).unwrap();
-        Ok((input, X03 {f1, f2, f3, f4, f5, f6}))
+        let (input, (min, max)) = tuple((|i| Vec3f::from_bytes(i, endian), |i| Vec3f::from_bytes(i, endian)))(input)?;
+        Ok((input, BBox {min, max}))
     }
 }
 
+/// 0x04000100, centre then radius
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct X04 {
-    f1: f32,
-    f2: f32,
-    f3: f32,
-    f4: f32,
-}
-impl FromBytes for X04 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X04, E>
+pub struct BSphere {
+    pub center: Vec3f,
+    pub radius: f32,
+}
+impl FromBytes for BSphere {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], BSphere, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        // let (input, (f1, f2, f3, f4)) = tuple((
-        //     le_f32::<&'a [u8], E>, le_f32, le_f32, le_f32
-        // ))(input).unwrap();
-        dbg!("before", input.len());
-        let (input, f1) = le_f32::<&'a [u8], ()>(input).unwrap();
-        let (input, f2) = le_f32::<&'a [u8], ()>(input).unwrap();
-        let (input, f3) = le_f32::<&'a [u8], ()>(input).unwrap();
-        let (input, f4) = le_f32::<&'a [u8], ()>(input).unwrap();
-        // dbg!(&f2);
-        Ok((input, X04 {f1, f2, f3, f4}))
+        let (input, (center, radius)) = tuple((|i| Vec3f::from_bytes(i, endian), f32(endian)))(input)?;
+        Ok((input, BSphere {center, radius}))
     }
 }
 
@@ -61,13 +47,11 @@ pub struct X06 {
 }
 impl X06{ pub fn new() -> X06 { Default::default() }}
 impl FromBytes for X06 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X06, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X06, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, num) = le_u32(input)?;  
-        let (input, normals) = count::<
-            &'a [u8], Vec3f, E, fn(&'a [u8]) -> IResult<&'a [u8], Vec3f, E>
-        >(Vec3f::from_bytes, num as usize)(input)?;
+        let (input, num) = u32(endian)(input)?;  
+        let (input, normals) = count(|i| Vec3f::from_bytes(i, endian), num as usize)(input)?;
         Ok((input, X06 {num, normals}))
     }
 }
@@ -79,13 +63,13 @@ pub struct X10 {
 }
 impl X10 { pub fn new() -> X10 { Default::default() }}
 impl FromBytes for X10 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X10, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X10, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, num) = le_u32::<&'a [u8], E>(input).unwrap();
+        let (input, num) = u32::<&'a [u8], E>(endian)(input)?;
         let (input, bytes) = count::<
             &'a [u8], u8, E, fn (&'a [u8]) -> IResult<&'a [u8], u8, E>
-        >(le_u8, num as usize)(input).unwrap();
+        >(le_u8, num as usize)(input)?;
 
         Ok((input, X10 {num, bytes}))
     }
@@ -96,53 +80,59 @@ pub struct X11 {
     nl: u8,
 }
 impl FromBytes for X11 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X11, E>
+    fn from_bytes<'a, E>(input: &'a [u8], _endian: Endianness) -> IResult<&'a [u8], X11, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, nl) = le_u8::<&'a [u8], E>(input).unwrap();
+        let (input, nl) = le_u8::<&'a [u8], E>(input)?;
         Ok((input, X11 {nl}))
     }
 }
 
+/// 0x17000100, after the meshes, 1 in every root seen
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct X17 {
-    one: u32,
+    pub one: u32,
 }
 impl FromBytes for X17 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X17, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X17, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, one) = le_u32::<&'a [u8], E>(input).unwrap();
+        let (input, one) = u32::<&'a [u8], E>(endian)(input)?;
         Ok((input, X17 {one}))
     }
 }
 
+/// 0x1d000100, after 0x17000100, a null and in every root seen another
+/// 0x1d000100 as its child, `col` fills in `children`
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct X1d {
-    null: u32,
+    pub null: u32,
+    pub children: Vec<X1d>,
 }
 impl FromBytes for X1d {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X1d, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X1d, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, null) = le_u32::<&'a [u8], E>(input).unwrap();
-        Ok((input, X1d {null}))
+        let (input, null) = u32::<&'a [u8], E>(endian)(input)?;
+        Ok((input, X1d {null, children: vec![]}))
     }
 }
 
+/// 0x21000100, the root's first child, a null then 7 u32s that were 32
+/// in the file this was written from
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct X21 {
-    null: u32,
-    thirty_two_1: u32,
-    thirty_two_2: u32,
-    thirty_two_3: u32,
-    thirty_two_4: u32,
-    thirty_two_5: u32,
-    thirty_two_6: u32,
-    thirty_two_7: u32,
+    pub null: u32,
+    pub thirty_two_1: u32,
+    pub thirty_two_2: u32,
+    pub thirty_two_3: u32,
+    pub thirty_two_4: u32,
+    pub thirty_two_5: u32,
+    pub thirty_two_6: u32,
+    pub thirty_two_7: u32,
 }
 impl FromBytes for X21 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X21, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X21, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
         let (
@@ -156,15 +146,15 @@ impl FromBytes for X21 {
                 thirty_two_6,
                 thirty_two_7,
         )) = tuple((
-            le_u32::<&'a [u8], E>,
-            le_u32,
-            le_u32,
-            le_u32,
-            le_u32,
-            le_u32,
-            le_u32,
-            le_u32,
-        ))(input).unwrap();
+            u32::<&'a [u8], E>(endian),
+            u32(endian),
+            u32(endian),
+            u32(endian),
+            u32(endian),
+            u32(endian),
+            u32(endian),
+            u32(endian),
+        ))(input)?;
         let x21 = X21 {
             null,
             thirty_two_1,
@@ -187,10 +177,10 @@ pub struct X26 {
     null: u32,
 }
 impl FromBytes for X26 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X26, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X26, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, null) = le_u32::<&'a [u8], E>(input).unwrap();
+        let (input, null) = u32::<&'a [u8], E>(endian)(input)?;
         Ok((input, X26 {null}))
     }
 }
@@ -199,28 +189,42 @@ pub struct X27 {
     null: u32,
 }
 impl FromBytes for X27 {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X27, E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X27, E>
         where
             E: ParseError<&'a [u8]> + Debug, {
-        let (input, null) = le_u32::<&'a [u8], E>(input).unwrap();
+        let (input, null) = u32::<&'a [u8], E>(endian)(input)?;
         Ok((input, X27 {null}))
     }
 }
 
+/// 0x00201200, first after the meshes, two floats
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct X00_20_12 {
-    f1: f32,
-    f2: f32,
+    pub f1: f32,
+    pub f2: f32,
 }
 impl FromBytes for X00_20_12  {
-    fn from_bytes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], X00_20_12  , E>
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X00_20_12  , E>
         where
             E: ParseError<&'a [u8]> + Debug, {
         let (input, (f1, f2)) = tuple((
-            le_f32::<&'a [u8], E>, le_f32
-        ))(input).unwrap();
+            f32::<&'a [u8], E>(endian), f32(endian)
+        ))(input)?;
         Ok((input, X00_20_12 {f1, f2}))
     }
 }
+/// 0x01201200, after 0x00201200 and the same size, kept as two u32s since
+/// nothing says they're floats
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct X01_20_12 {}
+pub struct X01_20_12 {
+    pub u1: u32,
+    pub u2: u32,
+}
+impl FromBytes for X01_20_12 {
+    fn from_bytes<'a, E>(input: &'a [u8], endian: Endianness) -> IResult<&'a [u8], X01_20_12, E>
+        where
+            E: ParseError<&'a [u8]> + Debug, {
+        let (input, (u1, u2)) = tuple((u32::<&'a [u8], E>(endian), u32(endian)))(input)?;
+        Ok((input, X01_20_12 {u1, u2}))
+    }
+}
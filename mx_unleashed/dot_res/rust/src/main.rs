@@ -6,11 +6,11 @@ use std::path::{Path, PathBuf};
 use nom::{
     bytes::complete::take,
     multi::{count, length_data, many0},
-    number::complete::le_u32,
+    number::complete::{le_u16, le_u32},
     sequence::tuple,
     IResult,
 };
-use zune_inflate::DeflateDecoder;
+use vfs::inflate_blocks;
 
 #[derive(Debug)]
 struct Dir {
@@ -35,13 +35,18 @@ impl Dir {
         ))
     }
 }
-fn parse_header<'a>(input: &'a [u8]) -> IResult<&'a [u8], (u32, u32)> {
+/// u32 1, block table size, block table, num of dirs, dir data size
+/// the block table is a u16 per zlib block, one less than its size, and a 0
+/// ends it, same as MX vs ATV
+fn parse_header<'a>(input: &'a [u8]) -> IResult<&'a [u8], (Vec<u16>, u32, u32)> {
     let (input, (_1, weird_bytes_size)) = tuple((le_u32, le_u32))(input)?;
-    let (input, _weird_bytes) = take(weird_bytes_size)(input)?;
+    let (input, weird_bytes) = take(weird_bytes_size)(input)?;
+    let (_, blocks) = many0(le_u16)(weird_bytes)?;
+    let blocks = blocks.into_iter().take_while(|b| *b != 0).collect();
     let (input, (maybe_num_of_dirs, dir_data_size)) = tuple((le_u32, le_u32))(input)?;
     // dbg!(_1, maybe_num_of_dirs, weird_bytes_size);
 
-    Ok((input, (maybe_num_of_dirs, dir_data_size)))
+    Ok((input, (blocks, maybe_num_of_dirs, dir_data_size)))
 }
 
 fn main() -> io::Result<()> {
@@ -54,7 +59,7 @@ fn main() -> io::Result<()> {
     _ = File::open(in_path)?.read_to_end(&mut v);
     let input: &[u8] = v.as_slice();
 
-    let (input, (maybe_num_of_dirs, dir_data_size)) = parse_header(input).unwrap();
+    let (input, (blocks, maybe_num_of_dirs, dir_data_size)) = parse_header(input).unwrap();
     // let (input, dirs) =
     //     count::<&[u8], Dir, (), for<'a> fn(&'a [u8]) -> IResult<&'a [u8], Dir, ()>>(
     //         Dir::parse, maybe_num_of_dirs as usize
@@ -66,17 +71,11 @@ fn main() -> io::Result<()> {
         Dir::parse,
     )(dir_data)
     .unwrap();
-    let mut decompressed: Vec<u8> = vec![];
-
-    for (idx, _byte) in input.iter().enumerate() {
-        if input[idx] == 0x78 {
-            // if input[idx+1] != 0x9c { () };
-            match &mut DeflateDecoder::new(&input[idx..]).decode_zlib() {
-                Ok(z) => decompressed.append(z),
-                Err(e) => (),
-            }
-        };
+    let (decompressed, problems) = inflate_blocks(input, &blocks);
+    for problem in problems.iter() {
+        println!(" BAD {}", problem);
     }
+    println!(" {} blocks {:#x} bytes", blocks.len(), decompressed.len());
 
     // println!("{:?}", dirs[0]);
     // println!("{:?}", dirs[1]);
@@ -93,19 +92,19 @@ fn main() -> io::Result<()> {
         let mut file_path_ = file_path.replace("\\", "/");
         out_path = out_path.join(file_path_);
         println!(" {:?}", out_path);
-        _ = create_dir_all(out_path.with_file_name(""));
-
-        let mut v: Vec<u8> = vec![];
 
+        let Some(data) = decompressed.get(file_start as usize..file_start as usize + file_size as usize) else {
+            println!(" BAD {:#x}+{:#x} is past the inflated data", file_start, file_size);
+            continue;
+        };
+        _ = create_dir_all(out_path.with_file_name(""));
         let mut file: File = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
             .open(&out_path)
             .unwrap();
-        file.write(
-            &decompressed.as_slice()[file_start as usize..file_start as usize + file_size as usize],
-        );
+        file.write_all(data)?;
         println!("success");
     }
 
@@ -0,0 +1,68 @@
+// crc-32 (the zlib one), built at compile time
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+/// (path as the dir table has it, the hash its starts entry has) read out
+/// of real archives, `rcf_hash` is tested against every one
+/// empty, nobody's had an archive to take one from yet, if `rcf list` on
+/// one prints no HASH lines every name found its entry and any will do
+pub const CONFIRMED: &[(&str, u32)] = &[];
+
+/// true once `CONFIRMED` has a pair in it, until then anything written
+/// with `rcf_hash` may not be found by the game
+pub fn rcf_hash_confirmed() -> bool {
+    !CONFIRMED.is_empty()
+}
+
+/// hash stored as the first field of each starts triple,
+/// crc-32 over the lowercased path with windows separators
+///
+/// a guess, it hasn't been checked against a real archive's starts table,
+/// if it's wrong no name finds its entry and `RcfArchive` pairs them all
+/// by table position, `Entry::hash_matches` is false for every one
+pub fn rcf_hash(path: &str) -> u32 {
+    !path
+        .bytes()
+        .map(|b| if b == b'/' { b'\\' } else { b.to_ascii_lowercase() })
+        .fold(!0u32, |c, b| TABLE[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        // the standard crc-32 check, so the table and the inversions are right
+        assert_eq!(rcf_hash("123456789"), 0xcbf4_3926);
+        assert_eq!(rcf_hash(""), 0);
+    }
+
+    #[test]
+    fn confirmed_pairs() {
+        for &(path, hash) in CONFIRMED {
+            assert_eq!(rcf_hash(path), hash, "{}", path);
+        }
+    }
+
+    #[test]
+    fn case_and_separators() {
+        let hash = rcf_hash("art\\cars\\homer_v.p3d");
+        assert_eq!(rcf_hash("ART/Cars/Homer_V.p3d"), hash);
+        assert_eq!(rcf_hash("art/cars/homer_v.p3d"), hash);
+        assert_ne!(rcf_hash("art/cars/homer_v.p3"), hash);
+    }
+}
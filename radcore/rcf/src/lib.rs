@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::{self, Read};
+use std::path::Path;
+use nom::{
+    IResult,
+    bytes::complete::take,
+    multi::{count, length_data},
+    number::{Endianness, complete::{le_u32, u32}},
+    sequence::tuple,
+};
+
+mod hash;
+pub use hash::{CONFIRMED, rcf_hash, rcf_hash_confirmed};
+mod pack;
+pub use pack::{pack, walk};
+
+pub const RADCORE: &[u8] = b"RADCORE CEMENT LIBRARY";
+pub const ATG_CORE: &[u8] = b"ATG CORE CEMENT LIBRARY";
+pub const ATG_CORE_HEADER_SIZE: usize = 0x3c;
+/// last byte of the version, 0 little endian, 1 big
+const ENDIAN_FLAG: usize = 0x23;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// SHAR, "RADCORE CEMENT LIBRARY"
+    /// 0x20  version (major, minor, 0, 1 if big endian), starts offset
+    /// starts: num, dir offset, 2 unknowns, (hash, offset, size) per file
+    /// dir:    num, null, (len, path\0, null) per file
+    Radcore,
+    /// CotT, "ATG CORE CEMENT LIBRARY"
+    /// 0x20  version (major, minor, 0, 1 if big endian), starts offset,
+    ///       starts size, dir offset, dir size, null, num
+    /// 0x3c  (hash, offset, size) per file
+    /// dir:  2 unknowns, then per file 3 unknowns, le len, path\0, 3 byte pad
+    AtgCore,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub hash: u32,
+    pub offset: u32,
+    pub size: u32,
+    /// false when no starts entry had the name's hash and the entry was
+    /// paired with its name by table position instead
+    pub hash_matches: bool,
+    /// false when the dir table had no name left for it, `name` is then
+    /// the hash as 8 hex digits
+    pub named: bool,
+}
+
+#[derive(Debug)]
+pub struct RcfArchive {
+    pub layout: Layout,
+    pub endian: Endianness,
+    pub entries: Vec<Entry>,
+    data: Vec<u8>,
+}
+
+// (hash, offset, size)
+type Start = (u32, u32, u32);
+
+fn bad(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, what.to_string())
+}
+fn u32_at(data: &[u8], at: usize, e: Endianness) -> Option<u32> {
+    let b: [u8; 4] = data.get(at..at + 4)?.try_into().ok()?;
+    Some(match e {
+        Endianness::Big => u32::from_be_bytes(b),
+        _ => u32::from_le_bytes(b),
+    })
+}
+
+/// (starts table offset, num, dir offset) if they all land inside `data`
+fn tables(data: &[u8], layout: Layout, e: Endianness) -> Option<(usize, usize, usize)> {
+    let (starts, num, dir) = match layout {
+        Layout::Radcore => {
+            let starts = u32_at(data, 0x24, e)? as usize;
+            (starts + 16, u32_at(data, starts, e)?, u32_at(data, starts + 4, e)?)
+        }
+        Layout::AtgCore => (ATG_CORE_HEADER_SIZE, u32_at(data, 0x38, e)?, u32_at(data, 0x2c, e)?),
+    };
+    let (num, dir) = (num as usize, dir as usize);
+    (starts.checked_add(num.checked_mul(12)?)? <= data.len() && dir < data.len()).then_some((starts, num, dir))
+}
+
+fn starts(input: &[u8], num: usize, e: Endianness) -> IResult<&[u8], Vec<Start>, ()> {
+    count(tuple((u32(e), u32(e), u32(e))), num)(input)
+}
+
+fn path(bytes: &[u8]) -> String {
+    let s = String::from_utf8_lossy(bytes.strip_suffix(b"\x00").unwrap_or(bytes)).to_string();
+    s.replace('\\', "/")
+}
+fn radcore_dir(input: &[u8], e: Endianness) -> IResult<&[u8], String, ()> {
+    let (input, path_slice) = length_data(u32(e))(input)?;
+    let (input, _idk) = u32(e)(input)?;
+    Ok((input, path(path_slice)))
+}
+fn atg_core_dir(input: &[u8]) -> IResult<&[u8], String, ()> {
+    let (input, _idk) = take(12usize)(input)?;
+    let (input, path_slice) = length_data(le_u32)(input)?;
+    let (input, _pad) = take(3usize)(input)?;
+    Ok((input, path(path_slice)))
+}
+/// as many names as the dir table has, up to `num`, CotT's stripped
+/// archives have fewer than there are files, or none
+fn dirs(input: &[u8], layout: Layout, num: usize, e: Endianness) -> Vec<String> {
+    let Ok((mut input, _num_null)) = tuple((u32::<&[u8], ()>(e), u32(e)))(input) else { return vec![] };
+    let mut names = vec![];
+    while names.len() < num {
+        let parsed = match layout {
+            Layout::Radcore => radcore_dir(input, e),
+            Layout::AtgCore => atg_core_dir(input),
+        };
+        let Ok((rest, name)) = parsed else { break };
+        names.push(name);
+        input = rest;
+    }
+    names
+}
+
+impl RcfArchive {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut data: Vec<u8> = vec![];
+        OpenOptions::new().read(true).open(path)?.read_to_end(&mut data)?;
+        Self::from_bytes(data)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<Self> {
+        let layout = if data.starts_with(RADCORE) {
+            Layout::Radcore
+        } else if data.starts_with(ATG_CORE) {
+            Layout::AtgCore
+        } else {
+            return Err(bad("not an RCF, no RADCORE/ATG CORE signature"));
+        };
+
+        // the version's last byte is the byte order, if it's something else
+        // or its tables don't fit the file read them both ways and keep the
+        // one that does, the byte swapped reading of a real offset is almost
+        // always the larger
+        let flagged = match data.get(ENDIAN_FLAG) {
+            Some(0) => Some(Endianness::Little),
+            Some(1) => Some(Endianness::Big),
+            _ => None,
+        };
+        let endian = match flagged.filter(|&e| tables(&data, layout, e).is_some()) {
+            Some(e) => e,
+            None => match (
+                tables(&data, layout, Endianness::Little),
+                tables(&data, layout, Endianness::Big),
+            ) {
+                (Some((_, ln, ld)), Some((_, bn, bd))) if bn + bd < ln + ld => Endianness::Big,
+                (Some(_), _) => Endianness::Little,
+                (None, Some(_)) => Endianness::Big,
+                (None, None) => return Err(bad("starts or dir table runs past the end of the archive")),
+            },
+        };
+        let (starts_offset, num, dir_offset) = tables(&data, layout, endian).unwrap();
+
+        let (_, starts) = starts(&data[starts_offset..], num, endian)
+            .map_err(|_| bad("starts table runs past the end of the archive"))?;
+        let names = dirs(&data[dir_offset..], layout, num, endian);
+
+        // names are paired with entries by hash, anything that doesn't hash
+        // falls back to table order, or offset order for ATG CORE archives
+        let by_hash: HashMap<u32, Start> = starts.iter().map(|s| (s.0, *s)).collect();
+        let mut by_order = starts.clone();
+        if layout == Layout::AtgCore {
+            by_order.sort_by_key(|&(_, offset, size)| (offset, size));
+        }
+        let mut entries: Vec<Entry> = names
+            .into_iter()
+            .zip(by_order.iter())
+            .map(|(name, &fallback)| {
+                let hash = rcf_hash(&name);
+                let found = by_hash.get(&hash);
+                let (hash, offset, size) = *found.unwrap_or(&fallback);
+                Entry { name, hash, offset, size, hash_matches: found.is_some(), named: true }
+            })
+            .collect();
+        // whatever no name claimed goes by its hash
+        let claimed: HashSet<(u32, u32, u32)> = entries.iter().map(|e| (e.hash, e.offset, e.size)).collect();
+        for &(hash, offset, size) in by_order.iter().filter(|s| !claimed.contains(s)) {
+            entries.push(Entry { name: format!("{:08x}", hash), hash, offset, size, hash_matches: true, named: false });
+        }
+
+        Ok(RcfArchive { layout, endian, entries, data })
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Entry> {
+        let hash = rcf_hash(name);
+        let name = name.replace('\\', "/");
+        self.entries
+            .iter()
+            .find(|e| e.hash_matches && e.hash == hash)
+            .or_else(|| self.entries.iter().find(|e| e.name.eq_ignore_ascii_case(&name)))
+    }
+
+    /// the entry's bytes, or None if it points past the end of the archive
+    pub fn read(&self, entry: &Entry) -> Option<&[u8]> {
+        let start = entry.offset as usize;
+        self.data.get(start..start + entry.size as usize)
+    }
+}
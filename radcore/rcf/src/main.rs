@@ -0,0 +1,287 @@
+use std::fs::{File, create_dir_all, OpenOptions};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use nom::number::Endianness;
+
+use rcf::{RcfArchive, Entry, Layout, pack, rcf_hash, rcf_hash_confirmed};
+
+/// `*` matches any run of characters (slashes included), `?` any one,
+/// case insensitive since the paths come from windows
+pub fn glob(pattern: &str, path: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let s: Vec<char> = path.to_lowercase().chars().collect();
+    let (mut pi, mut si) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while si < s.len() {
+        match p.get(pi) {
+            Some('*') => {
+                star = Some((pi, si));
+                pi += 1;
+            }
+            Some(&c) if c == '?' || c == s[si] => {
+                pi += 1;
+                si += 1;
+            }
+            _ => match star {
+                Some((sp, ss)) => {
+                    pi = sp + 1;
+                    si = ss + 1;
+                    star = Some((sp, ss + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+const UNRESOLVED: &str = "_unresolved";
+
+/// hashdict's "path hash" lines, by hash
+fn read_dict(path: &PathBuf) -> io::Result<HashMap<u32, String>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(text
+        .lines()
+        .filter_map(|line| line.rsplit_once(char::is_whitespace))
+        .filter_map(|(path, hash)| Some((u32::from_str_radix(hash.trim().trim_start_matches("0x"), 16).ok()?, path.trim().replace('\\', "/"))))
+        .collect())
+}
+
+/// the hash and extension of a name like "art/1a2b3c4d.p3d", what CotT's
+/// stripped archives and earlier extractions name files by
+fn hashed_name(name: &str) -> Option<(u32, &str)> {
+    let file = name.rsplit('/').next().unwrap_or(name);
+    let (stem, ext) = file.split_once('.').unwrap_or((file, ""));
+    let stem = stem.trim_start_matches("0x");
+    if stem.len() != 8 { return None; }
+    Some((u32::from_str_radix(stem, 16).ok()?, ext))
+}
+
+/// the name an entry is extracted as, and for an unnamed or hash named one
+/// whether the dictionary had it
+/// the dictionary's path gets the hashed name's extension if it has none,
+/// what it doesn't have goes in _unresolved/ under its hash
+fn entry_name(entry: &Entry, dict: Option<&HashMap<u32, String>>) -> (String, Option<bool>) {
+    let (hash, ext) = match (entry.named, hashed_name(&entry.name)) {
+        (false, _) => (entry.hash, ""),
+        (true, Some(hashed)) => hashed,
+        (true, None) => return (entry.name.clone(), None),
+    };
+    let Some(dict) = dict else { return (entry.name.clone(), Some(false)) };
+    match dict.get(&hash) {
+        Some(path) => {
+            if rcf_hash(path) != hash {
+                println!(" HASH {} is {:08x} not {:08x}, used anyway", path, rcf_hash(path), hash);
+            }
+            let has_ext = path.rsplit('/').next().is_some_and(|f| f.contains('.'));
+            let name = if ext.is_empty() || has_ext { path.clone() } else { format!("{}.{}", path, ext) };
+            (name, Some(true))
+        }
+        None => (format!("{}/{}", UNRESOLVED, entry.name), Some(false)),
+    }
+}
+
+/// where an entry goes under `out_path`, names come from the archive so a
+/// drive or leading slash is dropped and anything with `..` is refused
+fn out_file(out_path: &Path, name: &str) -> Option<PathBuf> {
+    let name = name.replace('\\', "/");
+    let name = match name.split_once(':') {
+        Some((drive, rest)) if drive.len() == 1 => rest.to_string(),
+        _ => name,
+    };
+    let mut path = out_path.to_path_buf();
+    for c in Path::new(&name).components() {
+        match c {
+            Component::Normal(part) => path.push(part),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => (),
+            Component::ParentDir => return None,
+        }
+    }
+    (path != out_path).then_some(path)
+}
+
+fn usage() -> io::Result<()> {
+    println!("rcf list <archive.rcf> [glob]");
+    println!("rcf extract <archive.rcf> <out_dir> [glob] [--dry-run] [--dict <dict.txt>]");
+    println!("rcf pack <dir> <archive.rcf> <radcore|atg> [--be] [--unchecked-hash]");
+    println!("rcf hash <path>..   (crc-32 of the lowercased path, unverified against a real archive)");
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let big_endian = args.iter().any(|a| a == "--be");
+    let unchecked_hash = args.iter().any(|a| a == "--unchecked-hash");
+    let dict_at = args.iter().position(|a| a == "--dict");
+    let dict = match dict_at.and_then(|i| args.get(i + 1)) {
+        Some(path) => Some(read_dict(&PathBuf::from(path))?),
+        None => None,
+    };
+    let args: Vec<&String> = args.iter().enumerate()
+        .filter(|(i, a)| !a.starts_with("--") && Some(*i) != dict_at.map(|d| d + 1))
+        .map(|(_, a)| a)
+        .collect();
+
+    let (list, archive, out_path, pattern) = match args.as_slice() {
+        [_, mode, archive, rest @ ..] if *mode == "list" => (true, archive, PathBuf::new(), rest.first()),
+        [_, mode, archive, out, rest @ ..] if *mode == "extract" => (false, archive, PathBuf::from(out), rest.first()),
+        [_, mode, dir, archive, layout] if *mode == "pack" => {
+            let layout = match layout.as_str() {
+                "radcore" => Layout::Radcore,
+                "atg" => Layout::AtgCore,
+                _ => return usage(),
+            };
+            // the game finds entries by their starts hash, a wrong one packs
+            // an archive that looks fine here and loads nothing
+            if !rcf_hash_confirmed() && !unchecked_hash {
+                println!(" BAD rcf_hash hasn't been checked against a real archive, the game may not find what's packed");
+                println!("check it with `rcf list` on a real archive and add a pair to rcf::CONFIRMED, or pack with --unchecked-hash");
+                return Ok(());
+            }
+            let endian = if big_endian { Endianness::Big } else { Endianness::Little };
+            return pack(&PathBuf::from(dir), &PathBuf::from(archive), layout, endian);
+        }
+        [_, mode, paths @ ..] if *mode == "hash" => {
+            for path in paths {
+                println!("{:08x} {}", rcf_hash(path), path);
+            }
+            return Ok(());
+        }
+        _ => return usage(),
+    };
+    let pattern = pattern.map(|p| p.as_str()).unwrap_or("*");
+
+    let rcf = RcfArchive::open(archive)?;
+    println!("{:?} {:?} {:?} {} entries", archive, rcf.layout, rcf.endian, rcf.entries.len());
+
+    let (mut matched, mut written, mut mismatched) = (0, 0, 0);
+    let (mut unnamed, mut recovered) = (0, 0);
+    for entry in rcf.entries.iter() {
+        if !entry.hash_matches {
+            mismatched += 1;
+            println!(" HASH {:08x} for {} isn't in the starts table", rcf_hash(&entry.name), entry.name);
+        }
+        let (name, found) = entry_name(entry, dict.as_ref());
+        if let Some(found) = found {
+            unnamed += 1;
+            recovered += found as usize;
+        }
+        if !glob(pattern, &name) {
+            continue;
+        }
+        matched += 1;
+
+        let Some(bytes) = rcf.read(entry) else {
+            println!(" BAD {} {:#x}+{:#x} is past the end of the archive", name, entry.offset, entry.size);
+            continue;
+        };
+        if list {
+            println!("{:08x} {:#010x} {:#010x} {}", entry.hash, entry.offset, entry.size, name);
+            continue;
+        }
+
+        let Some(out_dir) = out_file(&out_path, &name) else {
+            println!(" BAD {} would be written outside {:?}, skipped", name, out_path);
+            continue;
+        };
+        if !dry_run {
+            create_dir_all(out_dir.with_file_name(""))?;
+            let mut file: File = OpenOptions::new().write(true).create(true).truncate(true).open(&out_dir)?;
+            file.write_all(bytes)?;
+            written += 1;
+        }
+        println!(" GOOD {:?}", out_dir);
+    }
+
+    println!("{} of {} entries matched {:?}, {} written", matched, rcf.entries.len(), pattern, written);
+    if mismatched > 0 && mismatched == rcf.entries.iter().filter(|e| e.named).count() {
+        println!(" BAD no name hashed to an entry, rcf_hash is likely wrong for this archive, all {} were paired by table position", mismatched);
+    } else if mismatched > 0 {
+        println!("{} names didn't hash to an entry, those were paired by table position", mismatched);
+    }
+    match (unnamed, &dict) {
+        (0, _) => (),
+        (_, Some(_)) => println!("{} entries had no name or a hash for one, {} recovered from the dictionary, {} left under {}/",
+            unnamed, recovered, unnamed - recovered, UNRESOLVED),
+        (_, None) => println!("{} entries had no name or a hash for one and go by their hash, --dict names them", unnamed),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{remove_dir_all, write};
+
+    #[test]
+    fn hashed_names() {
+        assert_eq!(hashed_name("art/1a2b3c4d.p3d"), Some((0x1a2b3c4d, "p3d")));
+        assert_eq!(hashed_name("0x1A2B3C4D"), Some((0x1a2b3c4d, "")));
+        assert_eq!(hashed_name("art/face.p3d"), None);
+        assert_eq!(hashed_name("art/frontend.p3d"), None);
+    }
+
+    #[test]
+    fn out_files_stay_under_the_out_dir() {
+        let out = Path::new("out");
+        assert_eq!(out_file(out, "art/cars/homer.p3d"), Some(out.join("art/cars/homer.p3d")));
+        assert_eq!(out_file(out, "C:\\art\\.\\homer.p3d"), Some(out.join("art/homer.p3d")));
+        assert_eq!(out_file(out, "/etc/passwd"), Some(out.join("etc/passwd")));
+        assert_eq!(out_file(out, "art/../../evil"), None);
+        assert_eq!(out_file(out, "/"), None);
+    }
+
+    #[test]
+    fn evil_entry_from_an_archive() {
+        let root = std::env::temp_dir().join(format!("rcf_evil_{}", std::process::id()));
+        _ = remove_dir_all(&root);
+        create_dir_all(root.join("in/aa")).unwrap();
+        write(root.join("in/aa/evil"), b"evil").unwrap();
+        let out = root.join("out.rcf");
+        pack(&root.join("in"), &out, Layout::Radcore, Endianness::Little).unwrap();
+        // same length, so the dir table still lines up
+        let mut data = std::fs::read(&out).unwrap();
+        _ = remove_dir_all(&root);
+        let at = data.windows(7).position(|w| w == b"aa\\evil").unwrap();
+        data[at..at + 7].copy_from_slice(b"..\\evil");
+        let rcf = RcfArchive::from_bytes(data).unwrap();
+        assert_eq!(rcf.entries[0].name, "../evil");
+        assert_eq!(out_file(Path::new("out"), &rcf.entries[0].name), None);
+    }
+
+    #[test]
+    fn names_from_the_dict() {
+        let root = std::env::temp_dir().join(format!("rcf_dict_{}", std::process::id()));
+        _ = remove_dir_all(&root);
+        create_dir_all(root.join("in/art")).unwrap();
+        for name in ["art/frontend.p3d", "art/1a2b3c4d.p3d", "0badf00d.txt", "5ca1ab1e"] {
+            write(root.join("in").join(name), name).unwrap();
+        }
+        let out = root.join("out.rcf");
+        pack(&root.join("in"), &out, Layout::AtgCore, Endianness::Little).unwrap();
+        let rcf = RcfArchive::open(&out).unwrap();
+        _ = remove_dir_all(&root);
+        assert_eq!(rcf.layout, Layout::AtgCore);
+
+        let dict: HashMap<u32, String> = [
+            (0x1a2b3c4d, "art/crash/fur".to_string()),
+            (0x5ca1ab1e, "scripts/boss.lua".to_string()),
+        ].into_iter().collect();
+        let mut names: Vec<(String, Option<bool>)> = rcf.entries.iter().map(|e| entry_name(e, Some(&dict))).collect();
+        names.sort();
+        assert_eq!(names, vec![
+            ("_unresolved/0badf00d.txt".to_string(), Some(false)),
+            ("art/crash/fur.p3d".to_string(), Some(true)),
+            ("art/frontend.p3d".to_string(), None),
+            ("scripts/boss.lua".to_string(), Some(true)),
+        ]);
+
+        // without one hashed names stay as they are
+        let mut names: Vec<(String, Option<bool>)> = rcf.entries.iter().map(|e| entry_name(e, None)).collect();
+        names.sort();
+        assert_eq!(names[0], ("0badf00d.txt".to_string(), Some(false)));
+    }
+}
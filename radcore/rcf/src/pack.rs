@@ -0,0 +1,195 @@
+use std::fs::{read_dir, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use nom::number::Endianness;
+
+use crate::{rcf_hash, Layout, ATG_CORE, ATG_CORE_HEADER_SIZE, RADCORE};
+
+// the radcore header, its starts table and every file start on one of these
+pub const ALIGN: usize = 0x800;
+
+fn align(v: &mut Vec<u8>) {
+    v.resize(v.len().div_ceil(ALIGN) * ALIGN, 0);
+}
+
+/// every file under `dir`, relative to it, sorted so the output is stable
+pub fn walk(dir: &Path, root: &Path, v: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries: Vec<PathBuf> = read_dir(dir)?.map(|e| e.map(|e| e.path())).collect::<io::Result<_>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            walk(&path, root, v)?;
+        } else {
+            v.push(path.strip_prefix(root).unwrap().to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// builds an archive out of every file under `dir`, see `Layout` for what
+/// goes where, unknown fields are written as zero
+/// the starts hashes are `rcf_hash`'s, see `rcf_hash_confirmed`
+pub fn pack(dir: &Path, out_path: &Path, layout: Layout, endian: Endianness) -> io::Result<()> {
+    let u32_bytes = |u: u32| match endian {
+        Endianness::Big => u.to_be_bytes(),
+        _ => u.to_le_bytes(),
+    };
+
+    let mut files: Vec<PathBuf> = vec![];
+    walk(dir, dir, &mut files)?;
+    // the game looks entries up by hash, so keep the table sorted on it
+    files.sort_by_key(|p| rcf_hash(&p.to_string_lossy()));
+    let names: Vec<String> = files.iter().map(|p| p.to_string_lossy().replace('/', "\\")).collect();
+    let num = files.len();
+
+    let mut out: Vec<u8> = vec![];
+    let starts_at = match layout {
+        Layout::Radcore => {
+            out.extend_from_slice(RADCORE);
+            out.resize(0x20, 0);
+            out.extend_from_slice(&[1, 2, 0, (endian == Endianness::Big) as u8]); // 1.2 and the byte order flag
+            out.extend_from_slice(&u32_bytes(ALIGN as u32));
+            align(&mut out);
+
+            let dir_offset = ALIGN + 16 + 12 * num;
+            for u in [num as u32, dir_offset as u32, 0, 0] {
+                out.extend_from_slice(&u32_bytes(u));
+            }
+            out.resize(dir_offset, 0);
+
+            out.extend_from_slice(&u32_bytes(num as u32));
+            out.extend_from_slice(&u32_bytes(0));
+            for name in names.iter() {
+                out.extend_from_slice(&u32_bytes(name.len() as u32 + 1));
+                out.extend_from_slice(name.as_bytes());
+                out.push(0);
+                out.extend_from_slice(&u32_bytes(0));
+            }
+            ALIGN + 16
+        }
+        Layout::AtgCore => {
+            let starts_size = 12 * num;
+            let dir_offset = ATG_CORE_HEADER_SIZE + starts_size;
+            out.extend_from_slice(ATG_CORE);
+            out.resize(0x20, 0);
+            out.extend_from_slice(&[2, 1, 0, (endian == Endianness::Big) as u8]); // 2.1 and the byte order flag
+            out.resize(dir_offset, 0);
+
+            out.extend_from_slice(&u32_bytes(0));
+            out.extend_from_slice(&u32_bytes(0));
+            for name in names.iter() {
+                out.extend_from_slice(&[0; 12]);
+                out.extend_from_slice(&(name.len() as u32 + 1).to_le_bytes());
+                out.extend_from_slice(name.as_bytes());
+                out.push(0);
+                out.extend_from_slice(&[0; 3]);
+            }
+            let dir_size = out.len() - dir_offset;
+
+            let header = [
+                ATG_CORE_HEADER_SIZE as u32,
+                starts_size as u32,
+                dir_offset as u32,
+                dir_size as u32,
+                0,
+                num as u32,
+            ];
+            for (i, u) in header.iter().enumerate() {
+                out[0x24 + 4 * i..0x28 + 4 * i].copy_from_slice(&u32_bytes(*u));
+            }
+            ATG_CORE_HEADER_SIZE
+        }
+    };
+
+    for (i, path) in files.iter().enumerate() {
+        align(&mut out);
+        let start = out.len();
+        OpenOptions::new().read(true).open(dir.join(path))?.read_to_end(&mut out)?;
+
+        let triple = [rcf_hash(&names[i]), start as u32, (out.len() - start) as u32];
+        for (j, u) in triple.iter().enumerate() {
+            let at = starts_at + 12 * i + 4 * j;
+            out[at..at + 4].copy_from_slice(&u32_bytes(*u));
+        }
+        println!(" GOOD {:#010x} {:#010x} {}", start, out.len() - start, names[i]);
+    }
+    align(&mut out);
+
+    OpenOptions::new().write(true).create(true).truncate(true).open(out_path)?.write_all(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+    use crate::RcfArchive;
+
+    /// a few files in nested dirs, one of them empty, under a fresh temp dir
+    fn tree(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("rcf_{}_{}", name, std::process::id()));
+        _ = remove_dir_all(&root);
+        create_dir_all(root.join("in/art/cars")).unwrap();
+        write(root.join("in/art/cars/homer.p3d"), b"P3D\xff homer").unwrap();
+        write(root.join("in/art/frontend.p3d"), vec![7u8; ALIGN + 3]).unwrap();
+        write(root.join("in/empty.txt"), b"").unwrap();
+        root
+    }
+
+    fn round_trip(name: &str, layout: Layout, endian: Endianness) -> RcfArchive {
+        let root = tree(name);
+        let out = root.join("out.rcf");
+        pack(&root.join("in"), &out, layout, endian).unwrap();
+        let rcf = RcfArchive::open(&out).unwrap();
+
+        let mut files = vec![];
+        walk(&root.join("in"), &root.join("in"), &mut files).unwrap();
+        assert_eq!(rcf.entries.len(), files.len());
+        for file in files.iter() {
+            let name = file.to_string_lossy();
+            let entry = rcf.find(&name).unwrap();
+            assert!(entry.named && entry.hash_matches);
+            assert_eq!(entry.name, name);
+            assert_eq!(entry.offset as usize % ALIGN, 0);
+            assert_eq!(rcf.read(entry).unwrap(), std::fs::read(root.join("in").join(file)).unwrap());
+        }
+        _ = remove_dir_all(&root);
+        rcf
+    }
+
+    #[test]
+    fn radcore_round_trip() {
+        assert_eq!(round_trip("radcore", Layout::Radcore, Endianness::Little).layout, Layout::Radcore);
+    }
+
+    #[test]
+    fn atg_core_round_trip() {
+        assert_eq!(round_trip("atg", Layout::AtgCore, Endianness::Little).layout, Layout::AtgCore);
+    }
+
+    #[test]
+    fn big_endian_round_trip() {
+        assert_eq!(round_trip("radcore_be", Layout::Radcore, Endianness::Big).endian, Endianness::Big);
+        assert_eq!(round_trip("atg_be", Layout::AtgCore, Endianness::Big).endian, Endianness::Big);
+        assert_eq!(round_trip("radcore_le", Layout::Radcore, Endianness::Little).endian, Endianness::Little);
+        assert_eq!(round_trip("atg_le", Layout::AtgCore, Endianness::Little).endian, Endianness::Little);
+    }
+
+    #[test]
+    fn byte_order_without_the_flag() {
+        for (name, layout, endian) in [
+            ("radcore_noflag", Layout::Radcore, Endianness::Big),
+            ("atg_noflag", Layout::AtgCore, Endianness::Big),
+            ("atg_noflag_le", Layout::AtgCore, Endianness::Little),
+        ] {
+            let root = tree(name);
+            let out = root.join("out.rcf");
+            pack(&root.join("in"), &out, layout, endian).unwrap();
+            let mut data = std::fs::read(&out).unwrap();
+            data[0x23] = 0xff;
+            let rcf = RcfArchive::from_bytes(data).unwrap();
+            assert_eq!(rcf.endian, endian);
+            assert!(rcf.entries.iter().all(|e| e.hash_matches));
+            _ = remove_dir_all(&root);
+        }
+    }
+}
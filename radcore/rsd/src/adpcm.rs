@@ -0,0 +1,145 @@
+// the standard IMA tables, both radical's and the xbox's adpcm use them
+
+const INDEX_TABLE: [i32; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+
+const STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66, 73, 80, 88, 97,
+    107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449, 494, 544, 598, 658, 724, 796,
+    876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272, 2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871,
+    5358, 5894, 6484, 7132, 7845, 8630, 9493, 10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623,
+    27086, 29794, 32767,
+];
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ima {
+    hist: i32,
+    index: i32,
+}
+impl Ima {
+    fn new(hist: i16, index: u8) -> Self {
+        Ima { hist: hist as i32, index: (index as i32).clamp(0, 88) }
+    }
+    fn nibble(&mut self, nibble: u8) -> i16 {
+        let step = STEP_TABLE[self.index as usize];
+        let mut delta = step >> 3;
+        if nibble & 1 != 0 { delta += step >> 2; }
+        if nibble & 2 != 0 { delta += step >> 1; }
+        if nibble & 4 != 0 { delta += step; }
+        if nibble & 8 != 0 { delta = -delta; }
+        self.hist = (self.hist + delta).clamp(i16::MIN as i32, i16::MAX as i32);
+        self.index = (self.index + INDEX_TABLE[nibble as usize & 0xf]).clamp(0, 88);
+        self.hist as i16
+    }
+}
+
+/// RADP, 0x14 byte frames per channel, all the headers first
+/// header: u8 step index, u8 unknown, i16 hist
+/// then 0x10 bytes of nibbles per channel, low nibble first, 32 samples
+pub fn rad_ima(data: &[u8], channels: usize) -> Vec<i16> {
+    let frame_size = 0x14 * channels;
+    let mut out: Vec<i16> = vec![];
+    for frame in data.chunks_exact(frame_size) {
+        let mut decoded: Vec<Vec<i16>> = vec![];
+        for ch in 0..channels {
+            let header = &frame[4 * ch..4 * ch + 4];
+            let mut ima = Ima::new(i16::from_le_bytes([header[2], header[3]]), header[0]);
+            let nibbles = &frame[4 * channels + 0x10 * ch..4 * channels + 0x10 * (ch + 1)];
+            decoded.push(nibbles.iter().flat_map(|b| [ima.nibble(b & 0xf), ima.nibble(b >> 4)]).collect());
+        }
+        interleave(&decoded, &mut out);
+    }
+    out
+}
+
+/// XADP, the xbox's IMA, 0x24 byte blocks per channel
+/// header per channel: i16 hist, u8 step index, u8 unknown
+/// then 4 byte runs alternating between channels, low nibble first,
+/// 65 samples per block, the header's then 64 from the nibbles
+pub fn xbox_ima(data: &[u8], channels: usize) -> Vec<i16> {
+    let block_size = 0x24 * channels;
+    let mut out: Vec<i16> = vec![];
+    for block in data.chunks_exact(block_size) {
+        let mut imas: Vec<Ima> = (0..channels)
+            .map(|ch| Ima::new(i16::from_le_bytes([block[4 * ch], block[4 * ch + 1]]), block[4 * ch + 2]))
+            .collect();
+        let mut decoded: Vec<Vec<i16>> = imas.iter().map(|ima| vec![ima.hist as i16]).collect();
+        for (i, run) in block[4 * channels..].chunks_exact(4).enumerate() {
+            let ch = i % channels;
+            for b in run {
+                decoded[ch].push(imas[ch].nibble(b & 0xf));
+                decoded[ch].push(imas[ch].nibble(b >> 4));
+            }
+        }
+        interleave(&decoded, &mut out);
+    }
+    out
+}
+
+fn interleave(channels: &[Vec<i16>], out: &mut Vec<i16>) {
+    let len = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+    for i in 0..len {
+        out.extend(channels.iter().map(|c| c[i]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reference samples from python's audioop.adpcm2lin, a plain IMA/DVI
+    // decoder, fed the same nibbles high first with the header's hist and
+    // index as its state, plus the header sample in front
+    const NIBBLES_0: [u8; 32] = [
+        11, 48, 85, 122, 159, 196, 233, 14, 51, 88, 125, 162, 199, 236, 17, 54,
+        91, 128, 165, 202, 239, 20, 57, 94, 131, 168, 205, 242, 23, 60, 97, 134,
+    ];
+    const SAMPLES_0: [i16; 65] = [
+        -200, -208, -207, -206, -199, -191, -177, -187, -162, -218, -242, -175, -257, -290, -420, -651, -620,
+        -420, -238, -261, -24, -371, 323, 820, 368, 1601, 14, -1906, -5263, -3891, -2645, 2269, 6956, 2696,
+        8784, 9594, 8858, 16224, 11322, 6865, -429, -15137, -32768, 750, 13036, 1864, 25563, -14448, 30605,
+        32767, 29043, 25658, 10270, -20509, -32768, -12290, -32768, 28668, 32767, -751, 27918, 32767, 32767,
+        32767, 28672,
+    ];
+    const SAMPLES_1: [i16; 8] = [300, 325, 328, 331, 367, 402, 433, 488];
+
+    fn nibbles_1() -> Vec<u8> {
+        (0..32).map(|i| ((i * 91 + 5) & 0x77) as u8).collect()
+    }
+
+    #[test]
+    fn rad_ima_frame() {
+        // same nibble stream, the frame doesn't output its header sample
+        let mut frame = vec![4, 0];
+        frame.extend_from_slice(&(-200i16).to_le_bytes());
+        frame.extend_from_slice(&NIBBLES_0[..16]);
+        assert_eq!(rad_ima(&frame, 1), SAMPLES_0[1..33]);
+    }
+
+    #[test]
+    fn xbox_ima_mono_block() {
+        let mut block = vec![];
+        block.extend_from_slice(&(-200i16).to_le_bytes());
+        block.extend_from_slice(&[4, 0]);
+        block.extend_from_slice(&NIBBLES_0);
+        assert_eq!(xbox_ima(&block, 1), SAMPLES_0);
+    }
+
+    #[test]
+    fn xbox_ima_stereo_block() {
+        let mut block = vec![];
+        block.extend_from_slice(&(-200i16).to_le_bytes());
+        block.extend_from_slice(&[4, 0]);
+        block.extend_from_slice(&300i16.to_le_bytes());
+        block.extend_from_slice(&[10, 0]);
+        for (run_0, run_1) in NIBBLES_0.chunks(4).zip(nibbles_1().chunks(4)) {
+            block.extend_from_slice(run_0);
+            block.extend_from_slice(run_1);
+        }
+        let out = xbox_ima(&block, 2);
+        assert_eq!(out.len(), 2 * 65);
+        let left: Vec<i16> = out.iter().step_by(2).copied().collect();
+        let right: Vec<i16> = out.iter().skip(1).step_by(2).take(8).copied().collect();
+        assert_eq!(left, SAMPLES_0);
+        assert_eq!(right, SAMPLES_1);
+    }
+}
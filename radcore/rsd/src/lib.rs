@@ -0,0 +1,98 @@
+use nom::{
+    bytes::complete::{tag, take},
+    number::complete::{le_u32, le_u8},
+    sequence::tuple,
+    IResult,
+};
+
+mod adpcm;
+pub use adpcm::{rad_ima, xbox_ima};
+
+// every version we've seen starts its audio here
+pub const DATA_OFFSET: usize = 0x800;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// "PCM ", little endian
+    Pcm,
+    /// "PCMB", big endian
+    PcmBig,
+    /// "RADP", radical's IMA, PC
+    RadAdpcm,
+    /// "XADP", the xbox's IMA
+    XboxAdpcm,
+    Other([u8; 4]),
+}
+impl Codec {
+    fn from_bytes(b: &[u8]) -> Self {
+        match b {
+            b"PCM " => Codec::Pcm,
+            b"PCMB" => Codec::PcmBig,
+            b"RADP" => Codec::RadAdpcm,
+            b"XADP" => Codec::XboxAdpcm,
+            _ => Codec::Other(b.try_into().unwrap()),
+        }
+    }
+}
+
+/// RADSOUND stream
+/// 0x00  "RSD", version char, codec
+/// 0x08  channels, bits per sample, sample rate
+/// 0x800 audio
+#[derive(Debug)]
+pub struct Rsd<'a> {
+    pub version: char,
+    pub codec: Codec,
+    pub channels: u32,
+    pub bits: u32,
+    pub sample_rate: u32,
+    pub data: &'a [u8],
+}
+impl<'a> Rsd<'a> {
+    pub fn parse(input: &'a [u8]) -> IResult<&'a [u8], Self, ()> {
+        let (_, (_, version, codec)) = tuple((tag(b"RSD"), le_u8, take(4usize)))(input)?;
+        let (_, (channels, bits, sample_rate)) = tuple((le_u32, le_u32, le_u32))(&input[8..])?;
+        let (data, _header) = take(DATA_OFFSET)(input)?;
+        let codec = Codec::from_bytes(codec);
+        Ok((&[], Rsd { version: version as char, codec, channels, bits, sample_rate, data }))
+    }
+
+    /// interleaved 16 bit samples, None for codecs we can't decode
+    pub fn decode(&self) -> Option<Vec<i16>> {
+        let channels = self.channels as usize;
+        if channels == 0 {
+            return None;
+        }
+        Some(match (self.codec, self.bits) {
+            (Codec::Pcm, 16) => self.data.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect(),
+            (Codec::PcmBig, 16) => self.data.chunks_exact(2).map(|b| i16::from_be_bytes([b[0], b[1]])).collect(),
+            (Codec::Pcm, 8) => self.data.iter().map(|b| (*b as i16 - 0x80) << 8).collect(),
+            (Codec::RadAdpcm, _) => rad_ima(self.data, channels),
+            (Codec::XboxAdpcm, _) => xbox_ima(self.data, channels),
+            _ => return None,
+        })
+    }
+}
+
+/// a canonical 16 bit PCM wav
+pub fn wav(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+    let data_size = 2 * samples.len() as u32;
+    let block_align = 2 * channels;
+    let mut v: Vec<u8> = vec![];
+    v.extend_from_slice(b"RIFF");
+    v.extend_from_slice(&(36 + data_size).to_le_bytes());
+    v.extend_from_slice(b"WAVEfmt ");
+    v.extend_from_slice(&16u32.to_le_bytes());
+    v.extend_from_slice(&1u16.to_le_bytes());
+    v.extend_from_slice(&channels.to_le_bytes());
+    v.extend_from_slice(&sample_rate.to_le_bytes());
+    v.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    v.extend_from_slice(&block_align.to_le_bytes());
+    v.extend_from_slice(&16u16.to_le_bytes());
+    v.extend_from_slice(b"data");
+    v.extend_from_slice(&data_size.to_le_bytes());
+    for s in samples {
+        v.extend_from_slice(&s.to_le_bytes());
+    }
+    v
+}
@@ -0,0 +1,67 @@
+use std::fs::{create_dir_all, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use rsd::{wav, Rsd};
+
+fn decode(name: &str, bytes: &[u8], out_path: &Path) -> io::Result<bool> {
+    let Ok((_, rsd)) = Rsd::parse(bytes) else {
+        println!(" BAD {} isn't an RSD", name);
+        return Ok(false);
+    };
+    let Some(samples) = rsd.decode() else {
+        let codec = String::from_utf8_lossy(&bytes[4..8]);
+        println!(" BAD {} RSD{} {:?} {} bit isn't supported", name, rsd.version, codec, rsd.bits);
+        return Ok(false);
+    };
+
+    create_dir_all(out_path.with_file_name(""))?;
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(out_path)?
+        .write_all(&wav(rsd.channels as u16, rsd.sample_rate, &samples))?;
+    println!(
+        " GOOD {:?} {:?} {}ch {}hz {} samples",
+        out_path,
+        rsd.codec,
+        rsd.channels,
+        rsd.sample_rate,
+        samples.len() / rsd.channels as usize
+    );
+    Ok(true)
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let (in_path, out_dir) = match args.as_slice() {
+        [_, in_path, out_dir] => (PathBuf::from(in_path), PathBuf::from(out_dir)),
+        _ => {
+            println!("rsd <file.rsd|dir|archive> <out_dir>");
+            return Ok(());
+        }
+    };
+
+    // a single stream, or every .rsd in an extracted dir or archive
+    let (mut good, mut total) = (0, 0);
+    if in_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("rsd")) {
+        let bytes = vfs::read_path(&in_path)?;
+        let out_path = out_dir.join(in_path.file_name().unwrap()).with_extension("wav");
+        total += 1;
+        good += decode(&in_path.to_string_lossy(), &bytes, &out_path)? as usize;
+    } else {
+        let archive = vfs::open_archive(&in_path)?;
+        for name in archive.list() {
+            if !name.to_lowercase().ends_with(".rsd") {
+                continue;
+            }
+            total += 1;
+            let bytes = archive.read(&name)?;
+            good += decode(&name, &bytes, &out_dir.join(&name).with_extension("wav"))? as usize;
+        }
+    }
+    println!("{} of {} streams decoded", good, total);
+
+    Ok(())
+}
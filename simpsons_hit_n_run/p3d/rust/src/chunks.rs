@@ -22,6 +22,9 @@ pub const INTERSECT: u32 = 0x03_f0_00_03;
 pub const LOCATOR: u32 = 0x03_00_00_05;
 pub const TRIGGER: u32 = 0x03_00_00_06;
 
+pub const TEXT_BIBLE: u32 = 0x00_01_80_0D;
+pub const LANGUAGE: u32 = 0x00_01_80_0E;
+
 pub fn paris_chunk<'a>(input: &'a [u8]) -> IResult<&'a [u8], Chunk, ()> {
     let (input, header) = Header::paris(input).unwrap();
     let (input, ch) = Chunk::id(header.chunk_id).pariser().parse(input).unwrap();
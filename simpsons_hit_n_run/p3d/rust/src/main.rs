@@ -22,6 +22,10 @@ use utils::{Header, Matrix3f, Vec3f};
 mod chunks;
 use chunks::{CollisionVec, Cylinder, Fence, Intersect, OBbox, P3d, Skip, Sphere, Wall};
 use chunks::{COLLISIONVEC, CYLINDER, FENCE, INTERSECT, LOCATOR, OBBOX, P3D, SPHERE, WALL};
+use chunks::{LANGUAGE, TEXT_BIBLE};
+mod text_bible;
+use text_bible::{Language, TextBible};
+mod mission;
 
 fn chunk_paris<'a>(input: &'a [u8]) -> IResult<&'a [u8], ChunkType, ()> {
     const HEADER_SIZE: u32 = 12;
@@ -39,7 +43,7 @@ fn chunk_paris<'a>(input: &'a [u8]) -> IResult<&'a [u8], ChunkType, ()> {
     if let Chunk::Skip(_) = Chunk::id(header.chunk_id) {
         (input, chunk_slice) = take::<u32, &'a [u8], ()>(chunkslice_size)(input).unwrap();
         (chunk_slice, data_slice) = take::<u32, &'a [u8], ()>(dataslice_size)(chunk_slice).unwrap();
-    } else if let Chunk::P3d(_) = Chunk::id(header.chunk_id) {
+    } else if let Chunk::P3d(_) | Chunk::TextBible(_) = Chunk::id(header.chunk_id) {
         (input, chunk_slice) = take::<u32, &'a [u8], ()>(chunkslice_size)(input).unwrap();
         (chunk_slice, data_slice) = take::<u32, &'a [u8], ()>(dataslice_size)(chunk_slice).unwrap();
     } else {
@@ -58,7 +62,7 @@ fn chunk_paris<'a>(input: &'a [u8]) -> IResult<&'a [u8], ChunkType, ()> {
         .unwrap();
 
     let (remaining_chunkslice, sub_chunks) = many0(chunk_paris)(chunk_slice).unwrap();
-    assert_eq!(remaining_chunkslice, &[]);
+    assert_eq!(remaining_chunkslice, &[] as &[u8]);
 
     Ok((
         input,
@@ -84,6 +88,8 @@ enum Chunk {
     CollisionVec(CollisionVec),
     Intersect(Intersect),
     Locator(Locator),
+    TextBible(TextBible),
+    Language(Language),
     Skip(Skip),
 }
 impl Chunk {
@@ -97,6 +103,8 @@ impl Chunk {
             COLLISIONVEC => Chunk::CollisionVec(CollisionVec::new()),
             INTERSECT => Chunk::Intersect(Intersect::new()),
             LOCATOR => Chunk::Locator(Locator::new()),
+            TEXT_BIBLE => Chunk::TextBible(TextBible::new()),
+            LANGUAGE => Chunk::Language(Language::new()),
             _ => Chunk::Skip(Skip::new()),
         }
     }
@@ -110,6 +118,8 @@ impl Chunk {
             Chunk::CollisionVec(_) => CollisionVec::new().paris(),
             Chunk::Intersect(_) => Intersect::new().paris(),
             Chunk::Locator(_) => Locator::new().paris(),
+            Chunk::TextBible(_) => TextBible::new().paris(),
+            Chunk::Language(_) => Language::new().paris(),
             Chunk::Skip(_) => Skip::new().paris(),
         }
     }
@@ -158,7 +168,13 @@ fn get_chunks(ct: &ChunkType, v: &mut Vec<Chunk>) -> Vec<Chunk> {
 fn main() -> io::Result<()> {
     // std::env::set_var("RUST_BACKTRACE", "1");
 
-    // let args: Vec<String> = std::env::args().collect();
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("text-bible") => return text_bible::cli(&args[2..]),
+        Some("mission") => return mission::cli(&args[2..]),
+        _ => {}
+    }
+
     // let dir: &String = &args[1];
 
     let mut path: PathBuf = PathBuf::new();
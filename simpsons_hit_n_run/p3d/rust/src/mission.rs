@@ -0,0 +1,275 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_until},
+    character::complete::{char, multispace1, satisfy},
+    combinator::{map, opt, recognize, value},
+    multi::{many0, many0_count, separated_list0},
+    number::complete::recognize_float,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+use std::{
+    collections::BTreeSet,
+    fs::read_dir,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::triggers::Locator;
+use crate::{chunk_paris, get_chunks, red, Chunk};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Arg {
+    Str(String),
+    Int(i64),
+    Float(f32),
+    Ident(String),
+}
+impl Arg {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Arg::Str(s) | Arg::Ident(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Command {
+    pub line: usize,
+    pub name: String,
+    pub args: Vec<Arg>,
+}
+impl Command {
+    /// names of the locators this command points at, going by `LOCATOR_ARGS`
+    pub fn locators(&self) -> Vec<&str> {
+        LOCATOR_ARGS
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(&self.name))
+            .flat_map(|(_, indices)| indices.iter())
+            .filter_map(|&i| self.args.get(i).and_then(Arg::as_str))
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Script {
+    pub path: PathBuf,
+    pub commands: Vec<Command>,
+    /// lines that didn't parse as a command and were skipped
+    pub skipped: Vec<usize>,
+}
+
+// command name, indices of the args that name a locator
+const LOCATOR_ARGS: &[(&str, &[usize])] = &[
+    ("SetDestination", &[0]),
+    ("SetMissionResetPlayerInCar", &[0]),
+    ("SetMissionResetPlayerOutCar", &[0, 1]),
+    ("AddStageVehicle", &[1]),
+    ("MoveStageVehicle", &[1]),
+    ("AddStageWaypoint", &[0]),
+    ("AddStageCharacter", &[1]),
+    ("AddCollectible", &[0]),
+    ("AddSafeZone", &[0]),
+    ("AddNPC", &[1]),
+    ("AddCharacter", &[1]),
+    ("AddBonusMissionNPCWaypoint", &[1]),
+    ("AddPurchaseCarNPCWaypoint", &[1]),
+    ("SetInitialWalk", &[0]),
+];
+
+fn comment(input: &str) -> IResult<&str, (), ()> {
+    alt((
+        value((), pair(tag("//"), opt(is_not("\n")))),
+        value((), tuple((tag("/*"), take_until("*/"), tag("*/")))),
+    ))(input)
+}
+fn ws(input: &str) -> IResult<&str, (), ()> {
+    value((), many0_count(alt((value((), multispace1), comment))))(input)
+}
+fn ident(input: &str) -> IResult<&str, &str, ()> {
+    recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic() || c == '_'),
+        many0_count(satisfy(|c| c.is_ascii_alphanumeric() || c == '_')),
+    ))(input)
+}
+fn arg(input: &str) -> IResult<&str, Arg, ()> {
+    delimited(
+        ws,
+        alt((
+            map(delimited(char('"'), opt(is_not("\"")), char('"')), |s| {
+                Arg::Str(s.unwrap_or("").to_string())
+            }),
+            map(recognize_float, |s: &str| match s.parse::<i64>() {
+                Ok(i) => Arg::Int(i),
+                Err(_) => Arg::Float(s.parse().unwrap_or_default()),
+            }),
+            map(ident, |s| Arg::Ident(s.to_string())),
+        )),
+        ws,
+    )(input)
+}
+fn command(input: &str) -> IResult<&str, (&str, Vec<Arg>), ()> {
+    terminated(
+        pair(
+            ident,
+            preceded(
+                ws,
+                delimited(char('('), separated_list0(char(','), arg), preceded(ws, char(')'))),
+            ),
+        ),
+        opt(preceded(ws, char(';'))),
+    )(input)
+}
+
+pub fn parse_script(path: &Path, source: &str) -> Script {
+    let (mut commands, mut skipped): (Vec<Command>, Vec<usize>) = (vec![], vec![]);
+    let mut input = source;
+    // newlines are only counted over what's been read since the last command
+    let (mut line, mut counted) = (1, 0);
+    loop {
+        (input, _) = ws(input).unwrap();
+        if input.is_empty() {
+            break;
+        }
+        let at = source.len() - input.len();
+        line += source[counted..at].matches('\n').count();
+        counted = at;
+        match command(input) {
+            Ok((rest, (name, args))) => {
+                commands.push(Command {
+                    line,
+                    name: name.to_string(),
+                    args,
+                });
+                input = rest;
+            }
+            Err(_) => {
+                println!("{:?}:{} can't parse, skipping line", path, line);
+                skipped.push(line);
+                input = input.split_once('\n').map(|(_, rest)| rest).unwrap_or("");
+            }
+        }
+    }
+    Script {
+        path: path.to_path_buf(),
+        commands,
+        skipped,
+    }
+}
+
+fn files_with_ext(dir: &Path, exts: &[&str]) -> Vec<PathBuf> {
+    let mut v: Vec<PathBuf> = read_dir(dir)
+        .unwrap()
+        .filter_map(|p| p.ok().map(|p| p.path()))
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| exts.iter().any(|x| x.eq_ignore_ascii_case(e)))
+        })
+        .collect();
+    v.sort();
+    v
+}
+
+/// mission <script_dir> <p3d_dir>
+///
+/// parses every .mfk/.con in script_dir and checks the locator names they
+/// use against the locators (and their triggers) in the .p3d files of p3d_dir
+pub fn cli(args: &[String]) -> io::Result<()> {
+    let [script_dir, p3d_dir, ..] = args else {
+        println!("mission <script_dir> <p3d_dir>");
+        return Ok(());
+    };
+
+    let mut locators: Vec<Locator> = vec![];
+    for path in files_with_ext(Path::new(p3d_dir), &["p3d"]) {
+        let buf = red(&path);
+        let (_, c) = chunk_paris(buf.as_slice()).unwrap();
+        for cc in get_chunks(&c, &mut vec![]) {
+            if let Chunk::Locator(locator) = cc {
+                locators.push(locator);
+            }
+        }
+    }
+    let known: BTreeSet<&str> = locators
+        .iter()
+        .flat_map(|l| [l.name.as_str()].into_iter().chain(l.triggers.iter().map(|t| t.name.as_str())))
+        .collect();
+
+    let mut used: BTreeSet<String> = BTreeSet::new();
+    for path in files_with_ext(Path::new(script_dir), &["mfk", "con"]) {
+        let source = String::from_utf8_lossy(&red(&path)).to_string();
+        let script = parse_script(&path, &source);
+        println!(" GOOD {:?} {} commands", path, script.commands.len());
+
+        for command in script.commands.iter() {
+            for name in command.locators() {
+                if !known.contains(name) {
+                    println!(
+                        "MISSING {:?}:{} {}(\"{}\")",
+                        path, command.line, command.name, name
+                    );
+                }
+                used.insert(name.to_string());
+            }
+        }
+    }
+
+    for locator in locators.iter() {
+        if !used.contains(&locator.name) {
+            println!("UNUSED {}", locator.name);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Vec<Command> {
+        parse_script(Path::new("test.mfk"), source).commands
+    }
+
+    #[test]
+    fn arg_kinds() {
+        let commands = parse("AddStage(\"locator\", 3, -1.5, true, \"\");");
+        assert_eq!(commands[0].name, "AddStage");
+        assert_eq!(commands[0].args, vec![
+            Arg::Str("locator".to_string()),
+            Arg::Int(3),
+            Arg::Float(-1.5),
+            Arg::Ident("true".to_string()),
+            Arg::Str(String::new()),
+        ]);
+    }
+
+    #[test]
+    fn comments_and_empty_args() {
+        let commands = parse("// a line\nCloseStage( );/* a\nblock */ Foo(a, b )\nBar()");
+        let names: Vec<&str> = commands.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["CloseStage", "Foo", "Bar"]);
+        assert!(commands[0].args.is_empty() && commands[2].args.is_empty());
+        assert_eq!(commands[1].args.len(), 2);
+    }
+
+    #[test]
+    fn line_numbers() {
+        let script = parse_script(Path::new("test.mfk"), "A();\n\n/*\n*/ B();\nnot a command\nC();\n\n$bad");
+        let lines: Vec<usize> = script.commands.iter().map(|c| c.line).collect();
+        assert_eq!(lines, vec![1, 4, 6]);
+        assert_eq!(script.skipped, vec![5, 8]);
+    }
+
+    #[test]
+    fn locator_args() {
+        let commands = parse("setmissionresetplayeroutcar(\"a\", \"b\");AddNPC(\"apu\", \"npc_loc\");AddNPC(\"apu\", \"\");Other(\"x\");");
+        assert_eq!(commands[0].locators(), vec!["a", "b"]);
+        assert_eq!(commands[1].locators(), vec!["npc_loc"]);
+        assert!(commands[2].locators().is_empty());
+        assert!(commands[3].locators().is_empty());
+    }
+}
@@ -0,0 +1,455 @@
+use nom::{
+    bytes::complete::take,
+    multi::count,
+    number::complete::{le_u32, le_u8},
+    sequence::tuple,
+    IResult, Parser,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{create_dir_all, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::chunks::LANGUAGE;
+use crate::paris::{Par, Paris};
+use crate::utils::{pstring, pstring_bytes, Header};
+use crate::{chunk_paris, red, Chunk, ChunkType};
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TextBible {
+    pub name: String,
+    pub languages: Vec<Language>,
+}
+impl TextBible {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl Paris for TextBible {
+    fn paris<'a>(&self) -> Box<dyn Parser<&'a [u8], Chunk, ()>> {
+        Box::new(move |input| {
+            let (input, text_bible) = Self::par(input)?;
+            Ok((input, Chunk::TextBible(text_bible)))
+        })
+    }
+}
+impl Par for TextBible {
+    fn par(input: &[u8]) -> IResult<&[u8], Self, ()> {
+        let (input, name) = pstring(input)?;
+        Ok((
+            input,
+            Self {
+                name,
+                languages: vec![],
+            },
+        ))
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub hash: u32,
+    pub text: String,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Language {
+    pub name: String,
+    pub language: char,
+    pub modulo: u32,
+    pub strings: Vec<Entry>,
+}
+impl Language {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// swaps in the text of every entry whose hash is also in `table`,
+    /// returns how many were replaced
+    pub fn merge(&mut self, table: &Language) -> usize {
+        let mut replaced = 0;
+        for entry in table.strings.iter() {
+            match self.strings.iter_mut().find(|e| e.hash == entry.hash) {
+                Some(e) => {
+                    e.text = entry.text.clone();
+                    replaced += 1;
+                }
+                None => println!(
+                    "language {} has no string with hash {:08x}",
+                    self.language, entry.hash
+                ),
+            }
+        }
+        replaced
+    }
+    /// the chunk without children, so chunk_size is data_size
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut buffer: Vec<u8> = vec![];
+        let mut offsets: Vec<u32> = vec![];
+        for entry in self.strings.iter() {
+            offsets.push(buffer.len() as u32);
+            for u in entry.text.encode_utf16().chain([0]) {
+                buffer.extend_from_slice(&u.to_le_bytes());
+            }
+        }
+
+        let mut data = pstring_bytes(&self.name)?;
+        data.push(self.language as u8);
+        data.extend_from_slice(&(self.strings.len() as u32).to_le_bytes());
+        data.extend_from_slice(&self.modulo.to_le_bytes());
+        data.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+        for entry in self.strings.iter() {
+            data.extend_from_slice(&entry.hash.to_le_bytes());
+        }
+        for offset in offsets.iter() {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(&buffer);
+
+        let size = data.len() as u32 + 12;
+        let mut v = Header {
+            chunk_id: LANGUAGE,
+            data_size: size,
+            chunk_size: size,
+        }
+        .to_bytes();
+        v.extend_from_slice(&data);
+        Ok(v)
+    }
+}
+impl Paris for Language {
+    fn paris<'a>(&self) -> Box<dyn Parser<&'a [u8], Chunk, ()>> {
+        Box::new(move |input| {
+            let (input, language) = Self::par(input)?;
+            Ok((input, Chunk::Language(language)))
+        })
+    }
+}
+impl Par for Language {
+    fn par(input: &[u8]) -> IResult<&[u8], Self, ()> {
+        let (input, name) = pstring(input)?;
+        let (input, language) = le_u8(input)?;
+        let (input, (num_of_strings, modulo, buffer_size)) =
+            tuple((le_u32, le_u32, le_u32))(input)?;
+        let (input, hashes) = count(le_u32, num_of_strings as usize)(input)?;
+        let (input, offsets) = count(le_u32, num_of_strings as usize)(input)?;
+        let (input, buffer) = take(buffer_size)(input)?;
+
+        // offsets are in bytes into a buffer of null terminated utf-16 strings
+        let strings = hashes
+            .iter()
+            .zip(offsets.iter())
+            .map(|(&hash, &offset)| Entry {
+                hash,
+                text: utf16_at(buffer, offset as usize),
+            })
+            .collect();
+
+        Ok((
+            input,
+            Self {
+                name,
+                language: char::from(language),
+                modulo,
+                strings,
+            },
+        ))
+    }
+}
+
+fn utf16_at(buffer: &[u8], offset: usize) -> String {
+    let units: Vec<u16> = buffer
+        .get(offset..)
+        .unwrap_or(&[])
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .take_while(|&u| u != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+pub fn get_text_bibles(ct: &ChunkType, v: &mut Vec<TextBible>) {
+    match &ct.parent {
+        (Chunk::TextBible(text_bible), sub) => {
+            let mut text_bible = text_bible.clone();
+            for c in sub.iter() {
+                if let Chunk::Language(language) = &c.parent.0 {
+                    text_bible.languages.push(language.clone());
+                }
+            }
+            v.push(text_bible);
+        }
+        (_, sub) => {
+            for c in sub.iter() {
+                get_text_bibles(c, v);
+            }
+        }
+    }
+}
+
+/// copies one chunk and its children into `out`, swapping language chunks
+/// for their merged version and fixing up every chunk_size on the way back,
+/// a header whose sizes don't nest fails instead of being read past
+fn rebuild<'a>(
+    input: &'a [u8],
+    tables: &[Language],
+    out: &mut Vec<u8>,
+) -> IResult<&'a [u8], (), ()> {
+    let (input, header) = Header::paris(input)?;
+    if header.data_size < 12 || header.chunk_size < header.data_size {
+        return Err(nom::Err::Failure(()));
+    }
+    let (input, data) = take(header.data_size - 12)(input)?;
+    let (input, mut children) = take(header.chunk_size - header.data_size)(input)?;
+
+    let mut merged = None;
+    if header.chunk_id == LANGUAGE {
+        let (_, mut language) = Language::par(data)?;
+        if let Some(table) = tables.iter().find(|t| t.language == language.language) {
+            let replaced = language.merge(table);
+            match language.to_bytes() {
+                Ok(bytes) => {
+                    println!("language {} > {} strings replaced", language.language, replaced);
+                    merged = Some(bytes);
+                }
+                Err(e) => println!(" BAD language {} kept as is, {}", language.language, e),
+            }
+        }
+    }
+
+    let start = out.len();
+    match merged {
+        Some(bytes) => out.extend_from_slice(&bytes),
+        None => {
+            out.extend_from_slice(&header.to_bytes());
+            out.extend_from_slice(data);
+        }
+    }
+    while !children.is_empty() {
+        (children, _) = rebuild(children, tables, out)?;
+    }
+    let chunk_size = (out.len() - start) as u32;
+    out[start + 8..start + 12].copy_from_slice(&chunk_size.to_le_bytes());
+
+    Ok((input, ()))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+fn csv_rows(s: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+fn export(path: &Path, out_dir: &Path, format: &str) -> io::Result<()> {
+    let buf = red(path);
+    let (_, c) = chunk_paris(buf.as_slice()).unwrap();
+    let mut text_bibles = vec![];
+    get_text_bibles(&c, &mut text_bibles);
+
+    _ = create_dir_all(out_dir);
+    for text_bible in text_bibles.iter() {
+        for language in text_bible.languages.iter() {
+            let s = match format {
+                "csv" => {
+                    let mut s = String::from("language,hash,text\n");
+                    for entry in language.strings.iter() {
+                        s += &format!(
+                            "{},{},{}\n",
+                            language.language,
+                            entry.hash,
+                            csv_field(&entry.text)
+                        );
+                    }
+                    s
+                }
+                _ => serde_json::to_string_pretty(language)?,
+            };
+            // appended, bible names can have dots in them
+            let out_path = out_dir.join(format!("{}_{}.{}", text_bible.name, language.language, format));
+            OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&out_path)?
+                .write_all(s.as_bytes())?;
+            println!(" GOOD {:?} {} strings", out_path, language.strings.len());
+        }
+    }
+    Ok(())
+}
+
+fn read_tables(path: &Path) -> io::Result<Vec<Language>> {
+    let s = String::from_utf8_lossy(&red(path)).to_string();
+    if path.extension().is_some_and(|e| e == "json") {
+        return Ok(vec![serde_json::from_str(&s)?]);
+    }
+
+    let mut tables: Vec<Language> = vec![];
+    for row in csv_rows(&s).iter() {
+        let [language, hash, text] = row.as_slice() else {
+            continue;
+        };
+        let (Some(language), Ok(hash)) = (language.chars().next(), hash.parse::<u32>()) else {
+            continue; // header row
+        };
+        let entry = Entry {
+            hash,
+            text: text.clone(),
+        };
+        match tables.iter_mut().find(|t| t.language == language) {
+            Some(table) => table.strings.push(entry),
+            None => tables.push(Language {
+                language,
+                strings: vec![entry],
+                ..Language::new()
+            }),
+        }
+    }
+    Ok(tables)
+}
+
+fn import(path: &Path, table_paths: &[String], out_path: &Path) -> io::Result<()> {
+    let mut tables: Vec<Language> = vec![];
+    for table_path in table_paths.iter() {
+        tables.extend(read_tables(Path::new(table_path))?);
+    }
+
+    let buf = red(path);
+    let mut out: Vec<u8> = vec![];
+    let mut input = buf.as_slice();
+    while !input.is_empty() {
+        let at = buf.len() - input.len();
+        (input, _) = rebuild(input, &tables, &mut out).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("bad chunk in the tree at {:#x}", at))
+        })?;
+    }
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(out_path)?
+        .write_all(&out)
+}
+
+/// text-bible export <in.p3d> <out_dir> [json|csv]
+/// text-bible import <in.p3d> <out.p3d> <table.json|table.csv>..
+pub fn cli(args: &[String]) -> io::Result<()> {
+    match args {
+        [mode, path, out_dir, rest @ ..] if mode == "export" => {
+            let format = rest.first().map(String::as_str).unwrap_or("json");
+            export(Path::new(path), &PathBuf::from(out_dir), format)
+        }
+        [mode, path, out_path, tables @ ..] if mode == "import" && !tables.is_empty() => {
+            import(Path::new(path), tables, Path::new(out_path))
+        }
+        _ => {
+            println!("text-bible export <in.p3d> <out_dir> [json|csv]");
+            println!("text-bible import <in.p3d> <out.p3d> <table.json|table.csv>..");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunks::TEXT_BIBLE;
+
+    fn language() -> Language {
+        Language {
+            name: "english".to_string(),
+            language: 'E',
+            modulo: 3,
+            strings: vec![
+                Entry { hash: 1, text: "Bart".to_string() },
+                Entry { hash: 2, text: "d'oh, \"Homer\"".to_string() },
+            ],
+        }
+    }
+
+    #[test]
+    fn language_round_trip() {
+        let bytes = language().to_bytes().unwrap();
+        let (_, header) = Header::paris(&bytes).unwrap();
+        assert_eq!(header.data_size as usize, bytes.len());
+        let (rest, parsed) = Language::par(&bytes[12..]).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(parsed, language());
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn rebuild_keeps_language_children() {
+        let child = [Header { chunk_id: 0x1234, data_size: 16, chunk_size: 16 }.to_bytes(), vec![7; 4]].concat();
+        let mut lang = language().to_bytes().unwrap();
+        let size = (lang.len() + child.len()) as u32;
+        lang[8..12].copy_from_slice(&size.to_le_bytes());
+        lang.extend_from_slice(&child);
+
+        let name = pstring_bytes("bible").unwrap();
+        let data_size = 12 + name.len() as u32;
+        let mut bible = Header { chunk_id: TEXT_BIBLE, data_size, chunk_size: data_size + size }.to_bytes();
+        bible.extend_from_slice(&name);
+        bible.extend_from_slice(&lang);
+
+        let mut table = language();
+        table.strings = vec![Entry { hash: 1, text: "Lisa".to_string() }];
+        let mut out = vec![];
+        let (rest, _) = rebuild(&bible, &[table], &mut out).unwrap();
+        assert!(rest.is_empty());
+
+        let (_, c) = chunk_paris(&out).unwrap();
+        let mut bibles = vec![];
+        get_text_bibles(&c, &mut bibles);
+        assert_eq!(bibles[0].languages[0].strings[0].text, "Lisa");
+        assert_eq!(bibles[0].languages[0].strings[1].text, "d'oh, \"Homer\"");
+        assert!(out.ends_with(&child));
+        let (_, header) = Header::paris(&out).unwrap();
+        assert_eq!(header.chunk_size as usize, out.len());
+    }
+
+    #[test]
+    fn rebuild_bad_sizes() {
+        for (data_size, chunk_size) in [(8, 16), (16, 12)] {
+            let bytes = [Header { chunk_id: 0x1234, data_size, chunk_size }.to_bytes(), vec![0; 16]].concat();
+            assert!(rebuild(&bytes, &[], &mut vec![]).is_err());
+        }
+    }
+
+    #[test]
+    fn pstring_too_long() {
+        assert_eq!(pstring_bytes(&"a".repeat(251)).unwrap()[0], 252);
+        assert!(pstring_bytes(&"a".repeat(252)).is_err());
+    }
+}
@@ -1,12 +1,12 @@
 use nom::{
     error::ParseError,
-    multi::count,
-    number::complete::{le_f32, le_u32},
+    multi::{count, length_data},
+    number::complete::{le_f32, le_u32, le_u8},
     sequence::tuple,
     IResult,
 };
 use std::f32::consts::{PI, TAU};
-use std::{default::Default, fmt::Debug};
+use std::{default::Default, fmt::Debug, io};
 // use ndarray::{arr2, arr3};
 
 #[derive(Debug)]
@@ -28,6 +28,31 @@ impl Header {
             },
         ))
     }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(12);
+        v.extend_from_slice(&self.chunk_id.to_le_bytes());
+        v.extend_from_slice(&self.data_size.to_le_bytes());
+        v.extend_from_slice(&self.chunk_size.to_le_bytes());
+        v
+    }
+}
+
+/// p3d strings are a u8 length followed by the bytes, null padded to 4
+pub fn pstring(input: &[u8]) -> IResult<&[u8], String, ()> {
+    let (input, bytes) = length_data(le_u8::<&[u8], ()>)(input)?;
+    let string = String::from_utf8_lossy(bytes);
+    Ok((input, string.trim_matches(char::from(0)).to_string()))
+}
+/// the length byte counts the padding, so 251 bytes is as long as one gets
+pub fn pstring_bytes(s: &str) -> io::Result<Vec<u8>> {
+    let len = (s.len() + 4) & !3;
+    let len_byte = u8::try_from(len).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("{:?} is too long for a p3d string", s))
+    })?;
+    let mut v = vec![len_byte];
+    v.extend_from_slice(s.as_bytes());
+    v.resize(len + 1, 0);
+    Ok(v)
 }
 
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
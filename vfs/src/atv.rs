@@ -0,0 +1,104 @@
+use nom::{
+    bytes::complete::take,
+    multi::{count, length_data, many0},
+    number::complete::{le_u16, le_u32},
+    sequence::tuple,
+    IResult,
+};
+use std::io;
+use crate::{bad, inflate_blocks, Table};
+
+const CHUNK_SIZE: usize = 0x800;
+
+// (path, u32, u32)
+type Dir = (String, u32, u32);
+
+fn path(bytes: &[u8]) -> String {
+    let bytes = bytes.split(|b| *b == 0).next().unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).to_string()
+}
+fn dir(input: &[u8]) -> IResult<&[u8], Dir, ()> {
+    let (input, path_bytes) = length_data(le_u32)(input)?;
+    let (input, (a, b)) = tuple((le_u32, le_u32))(input)?;
+    Ok((input, (path(path_bytes), a, b)))
+}
+
+/// MX vs ATV .pak
+/// u32 num, then per file a 0x26 byte path, 0x3e unknown bytes, start, size
+/// starts are relative to the end of the table
+pub fn pak(input: &[u8]) -> io::Result<Table> {
+    fn entry(input: &[u8]) -> IResult<&[u8], (String, usize, usize), ()> {
+        let (input, path_bytes) = take(0x26usize)(input)?;
+        let (input, _unknown) = take(0x3eusize)(input)?;
+        let (input, (start, size)) = tuple((le_u32, le_u32))(input)?;
+        Ok((input, (path(path_bytes), start as usize, size as usize)))
+    }
+    let (input, num_of_dirs) = le_u32::<&[u8], ()>(input).map_err(|_| bad("empty pak"))?;
+    let (input, entries) =
+        count(entry, num_of_dirs as usize)(input).map_err(|_| bad("pak table runs past the end of the file"))?;
+    Ok(Table::new(entries, input.to_vec()))
+}
+
+/// MX vs ATV .res
+/// 4 u32 header, u32 block table size, u16 per block, num of dirs, dir size
+/// dir:  (len, path, offset, size) per file, offsets index the inflated data
+/// then zlib blocks like MX Unleashed's .res, a 0 ends the table
+pub fn atv_res(input: &[u8]) -> io::Result<Table> {
+    fn header(input: &[u8]) -> IResult<&[u8], (Vec<u16>, Vec<Dir>), ()> {
+        let (input, _header) = tuple((le_u32, le_u32, le_u32, le_u32))(input)?;
+        let (input, zlib_offset_data) = length_data(le_u32)(input)?;
+        let (_, zlib_offsets) = many0(le_u16)(zlib_offset_data)?;
+        let (input, (num_of_dirs, _dir_section_size)) = tuple((le_u32, le_u32))(input)?;
+        let (input, dirs) = count(dir, num_of_dirs as usize)(input)?;
+        Ok((input, (zlib_offsets.into_iter().take_while(|o| *o != 0).collect(), dirs)))
+    }
+    let (compressed_data, (blocks, dirs)) =
+        header(input).map_err(|_| bad("res header runs past the end of the file"))?;
+
+    let (decompressed, problems) = inflate_blocks(compressed_data, &blocks);
+    if let Some(problem) = problems.first() {
+        return Err(bad(problem));
+    }
+
+    let entries = dirs.into_iter().map(|(p, offset, size)| (p, offset as usize, size as usize)).collect();
+    Ok(Table::new(entries, decompressed))
+}
+
+/// MX vs ATV .xbr
+/// 6 u32 header, the 2nd is the num of dirs
+/// dir:  (len, path, size, offset) per file, offsets count 0x800 chunks
+pub fn xbr(data: &[u8]) -> io::Result<Table> {
+    let (input, (_, num_of_dirs, _, _, _dir_section_size, _)) =
+        tuple((le_u32::<&[u8], ()>, le_u32, le_u32, le_u32, le_u32, le_u32))(data)
+            .map_err(|_| bad("xbr header runs past the end of the file"))?;
+    let (_, dirs) =
+        count(dir, num_of_dirs as usize)(input).map_err(|_| bad("xbr dir runs past the end of the file"))?;
+    let entries = dirs.into_iter().map(|(p, size, offset)| (p, offset as usize * CHUNK_SIZE, size as usize)).collect();
+    Ok(Table::new(entries, data.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{stored, Vfs};
+
+    fn res(blocks: &[Vec<u8>]) -> Vec<u8> {
+        let table: Vec<u8> = blocks.iter().map(|z| z.len() as u16 - 1).chain([0]).flat_map(|b| b.to_le_bytes()).collect();
+        let u = |v: &[u32]| v.iter().flat_map(|u| u.to_le_bytes()).collect::<Vec<u8>>();
+        let dirs = [u(&[5]), b"a.txt".to_vec(), u(&[2, 4]), u(&[5]), b"b.txt".to_vec(), u(&[6, 1])].concat();
+        [u(&[3, 0, 0, 0, table.len() as u32]), table, u(&[2, dirs.len() as u32]), dirs, blocks.concat()].concat()
+    }
+
+    #[test]
+    fn res_blocks() {
+        let (one, two) = (stored(b"abcd"), stored(b"efg"));
+        let table = atv_res(&res(&[one.clone(), two.clone()])).unwrap();
+        assert_eq!(table.read("a.txt").unwrap().as_ref(), b"cdef");
+        assert_eq!(table.read("B.TXT").unwrap().as_ref(), b"g");
+
+        let mut broken = two.clone();
+        broken[2] = 0x07;
+        let e = atv_res(&res(&[one.clone(), broken])).unwrap_err();
+        assert_eq!(e.to_string(), format!("zlib block 1 {:#x}..{:#x} didn't inflate", one.len(), one.len() + two.len()));
+    }
+}
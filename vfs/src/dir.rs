@@ -0,0 +1,34 @@
+use std::borrow::Cow;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{normalize, not_found, read_file, Vfs};
+
+/// an already extracted archive, so tools don't care which they're given
+#[derive(Debug)]
+pub struct DirVfs {
+    pub root: PathBuf,
+}
+impl DirVfs {
+    pub fn new(root: &Path) -> Self {
+        DirVfs { root: root.to_path_buf() }
+    }
+}
+impl Vfs for DirVfs {
+    fn list(&self) -> Vec<String> {
+        let mut files: Vec<PathBuf> = vec![];
+        _ = rcf::walk(&self.root, &self.root, &mut files);
+        files.iter().map(|p| p.to_string_lossy().to_string()).collect()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Cow<'_, [u8]>> {
+        let exact = self.root.join(path.replace('\\', "/"));
+        if exact.is_file() {
+            return read_file(&exact).map(Cow::Owned);
+        }
+        // extracted on a case sensitive filesystem, fall back to a search
+        let want = normalize(path);
+        let found = self.list().into_iter().find(|p| normalize(p) == want).ok_or_else(|| not_found(path))?;
+        read_file(&self.root.join(found)).map(Cow::Owned)
+    }
+}
@@ -0,0 +1,129 @@
+use std::borrow::Cow;
+use std::fs::OpenOptions;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+mod dir;
+pub use dir::DirVfs;
+mod mx;
+pub use mx::{inflate_blocks, mx_res, psr};
+mod atv;
+pub use atv::{atv_res, pak, xbr};
+mod rcf_vfs;
+
+pub use rcf::RcfArchive;
+
+/// read only view of an archive, paths are `/` separated and matched case
+/// insensitively since they all come from windows
+pub trait Vfs {
+    /// every file in the archive, as stored
+    fn list(&self) -> Vec<String>;
+
+    /// a file's bytes, borrowed from the archive where it can be
+    fn read(&self, path: &str) -> io::Result<Cow<'_, [u8]>>;
+
+    /// a reader over a file, for parsers that want `Read`
+    fn open(&self, path: &str) -> io::Result<Cursor<Cow<'_, [u8]>>> {
+        Ok(Cursor::new(self.read(path)?))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let path = normalize(path);
+        self.list().iter().any(|p| normalize(p) == path)
+    }
+}
+
+pub(crate) fn bad(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, what.to_string())
+}
+pub(crate) fn not_found(path: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} isn't in the archive", path))
+}
+pub(crate) fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches('/').to_lowercase()
+}
+/// zlib with one stored block, so the tests don't need a compressor
+#[cfg(test)]
+pub(crate) fn stored(data: &[u8]) -> Vec<u8> {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &x in data {
+        a = (a + x as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    let len = data.len() as u16;
+    [&[0x78, 0x01, 0x01][..], &len.to_le_bytes(), &(!len).to_le_bytes(), data, &((b << 16) | a).to_be_bytes()].concat()
+}
+pub(crate) fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut buf: Vec<u8> = vec![];
+    OpenOptions::new().read(true).open(path)?.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// the archives that keep a plain (path, offset, size) table over one blob,
+/// compressed ones are inflated up front so reads can still borrow
+#[derive(Debug)]
+pub struct Table {
+    pub entries: Vec<(String, usize, usize)>,
+    data: Vec<u8>,
+}
+impl Table {
+    pub(crate) fn new(entries: Vec<(String, usize, usize)>, data: Vec<u8>) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|(path, offset, size)| (path.replace('\\', "/"), offset, size))
+            .collect();
+        Table { entries, data }
+    }
+}
+impl Vfs for Table {
+    fn list(&self) -> Vec<String> {
+        self.entries.iter().map(|e| e.0.clone()).collect()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Cow<'_, [u8]>> {
+        let want = normalize(path);
+        let &(_, offset, size) = self
+            .entries
+            .iter()
+            .find(|e| normalize(&e.0) == want)
+            .ok_or_else(|| not_found(path))?;
+        self.data
+            .get(offset..offset + size)
+            .map(Cow::Borrowed)
+            .ok_or_else(|| bad(&format!("{} {:#x}+{:#x} is past the end of the archive", path, offset, size)))
+    }
+}
+
+/// picks the backend from the extension, `.res` is told apart by its first
+/// u32, 3 for MX vs ATV and 1 for MX Unleashed
+pub fn open_archive(path: &Path) -> io::Result<Box<dyn Vfs>> {
+    if path.is_dir() {
+        return Ok(Box::new(DirVfs::new(path)));
+    }
+    let data = read_file(path)?;
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    Ok(match ext.as_str() {
+        "res" if data.starts_with(&3u32.to_le_bytes()) => Box::new(atv_res(&data)?),
+        "res" => Box::new(mx_res(&data)?),
+        "psr" => Box::new(psr(&data)?),
+        "pak" => Box::new(pak(&data)?),
+        "xbr" => Box::new(xbr(&data)?),
+        _ => Box::new(RcfArchive::from_bytes(data)?),
+    })
+}
+
+/// reads a file off disk, or out of an archive when part of the path is
+/// one, so `/tmp/cott/default.rcf/package/L1_E1/L1_E1_R1_ext.p3d` works
+/// the same as the extracted copy
+pub fn read_path(path: &Path) -> io::Result<Vec<u8>> {
+    if path.is_file() {
+        return read_file(path);
+    }
+    let archive = path
+        .ancestors()
+        .skip(1)
+        .find(|p| p.is_file())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{:?} doesn't exist", path)))?;
+    let inner = path.strip_prefix(archive).unwrap().to_string_lossy().to_string();
+    Ok(open_archive(archive)?.read(&inner)?.into_owned())
+}
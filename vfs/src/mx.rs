@@ -0,0 +1,160 @@
+use nom::{
+    bytes::complete::take,
+    multi::{length_data, many0},
+    number::complete::{le_u16, le_u32},
+    sequence::tuple,
+    IResult,
+};
+use std::io;
+use zune_inflate::DeflateDecoder;
+
+use crate::{bad, Table};
+
+// psr files and the dir section both start on one of these
+const FILE_STRIDE: usize = 0x800;
+
+fn path(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes.strip_suffix(b"\x00").unwrap_or(bytes)).to_string()
+}
+
+/// (path, a, b), both .res and .psr dirs are a length prefixed path and
+/// two u32s
+fn dir(input: &[u8]) -> IResult<&[u8], (String, u32, u32), ()> {
+    let (input, path_bytes) = length_data(le_u32)(input)?;
+    let (input, (a, b)) = tuple((le_u32, le_u32))(input)?;
+    Ok((input, (path(path_bytes), a, b)))
+}
+
+/// (block table, dir data), the zlib blocks are what's left
+fn res_header(input: &[u8]) -> IResult<&[u8], (Vec<u16>, &[u8]), ()> {
+    let (input, (_, block_table_size)) = tuple((le_u32, le_u32))(input)?;
+    let (input, block_table) = take(block_table_size)(input)?;
+    let (_, blocks) = many0(le_u16)(block_table)?;
+    let (input, (_num_of_dirs, dir_data_size)) = tuple((le_u32, le_u32))(input)?;
+    let (input, dir_data) = take(dir_data_size)(input)?;
+    Ok((input, (blocks.into_iter().take_while(|b| *b != 0).collect(), dir_data)))
+}
+
+/// inflates the table's blocks in order, the first one's size is the size
+/// they all inflate to bar the last, a later block that doesn't inflate or
+/// comes out another size is zero filled to it so the files after it still
+/// line up, what went wrong comes back beside the data
+/// nothing says what size that is if the first block doesn't inflate, so
+/// that stops it with nothing inflated
+pub fn inflate_blocks(input: &[u8], blocks: &[u16]) -> (Vec<u8>, Vec<String>) {
+    let (mut decompressed, mut problems) = (vec![], vec![]);
+    let mut block_size = None;
+    let mut start = 0usize;
+    for (i, b) in blocks.iter().enumerate() {
+        let end = start + *b as usize + 1;
+        let last = i == blocks.len() - 1;
+        let z = match input.get(start..end) {
+            Some(block) => DeflateDecoder::new(block).decode_zlib().ok(),
+            None => None,
+        };
+        match (z, block_size) {
+            (Some(mut z), None) => {
+                block_size = Some(z.len());
+                decompressed.append(&mut z);
+            }
+            (Some(mut z), Some(size)) if z.len() == size || (last && z.len() < size) => decompressed.append(&mut z),
+            (Some(mut z), Some(size)) => {
+                problems.push(format!("zlib block {} {:#x}..{:#x} inflated to {:#x} not {:#x}", i, start, end, z.len(), size));
+                z.resize(size, 0);
+                decompressed.append(&mut z);
+            }
+            (None, None) => {
+                problems.push(format!("zlib block {} {:#x}..{:#x} didn't inflate, so the block size isn't known", i, start, end));
+                return (vec![], problems);
+            }
+            (None, Some(size)) => {
+                problems.push(format!("zlib block {} {:#x}..{:#x} didn't inflate", i, start, end));
+                decompressed.resize(decompressed.len() + size, 0);
+            }
+        }
+        start = end;
+    }
+    if start != input.len() {
+        problems.push(format!("zlib blocks end at {:#x} of {:#x}", start, input.len()));
+    }
+    (decompressed, problems)
+}
+
+/// MX Unleashed .res
+/// u32 1, block table size, u16 per block, num of dirs, dir data size
+/// dir:  (len, path, start, size) per file, starts index the inflated data
+/// then zlib blocks back to back, each one byte longer than its table entry,
+/// a 0 ends the table, all inflate to the first one's size bar the last
+pub fn mx_res(input: &[u8]) -> io::Result<Table> {
+    let (input, (blocks, dir_data)) = res_header(input).map_err(|_| bad("res header runs past the end of the file"))?;
+    let (_, dirs) = many0(dir)(dir_data).map_err(|_| bad("bad res dir"))?;
+
+    let (decompressed, problems) = inflate_blocks(input, &blocks);
+    if let Some(problem) = problems.first() {
+        return Err(bad(problem));
+    }
+
+    let entries = dirs.into_iter().map(|(p, start, size)| (p, start as usize, size as usize)).collect();
+    Ok(Table::new(entries, decompressed))
+}
+
+/// MX Unleashed .psr
+/// 6 u32 header, the 5th is the dir section size
+/// dir:  (len, path, size, unknown) per file
+/// files follow the dir in order, each padded to 0x800
+pub fn psr(data: &[u8]) -> io::Result<Table> {
+    let header_section_size = 4 * 6;
+    let (input, (_, _, _, _, dir_section_size, _)) =
+        tuple((le_u32::<&[u8], ()>, le_u32, le_u32, le_u32, le_u32, le_u32))(data)
+            .map_err(|_| bad("psr header runs past the end of the file"))?;
+    let dir_section_size = dir_section_size as usize;
+    let dir_data = input.get(..dir_section_size).ok_or_else(|| bad("psr dir runs past the end of the file"))?;
+    let (_, dirs) = many0(dir)(dir_data).map_err(|_| bad("bad psr dir"))?;
+
+    let mut offset = (header_section_size + dir_section_size).div_ceil(FILE_STRIDE) * FILE_STRIDE;
+    let mut entries = vec![];
+    for (p, size, _unknown) in dirs {
+        let size = size as usize;
+        entries.push((p, offset, size));
+        offset += size.div_ceil(FILE_STRIDE) * FILE_STRIDE;
+    }
+    Ok(Table::new(entries, data.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stored;
+
+    #[test]
+    fn bad_blocks_are_zero_filled() {
+        let (one, two, last) = (stored(b"abcd"), stored(b"efgh"), stored(b"ij"));
+        let block = |z: &Vec<u8>| z.len() as u16 - 1;
+        let blocks = [block(&one), block(&two), block(&last)];
+
+        let input = [one.clone(), two.clone(), last.clone()].concat();
+        assert_eq!(inflate_blocks(&input, &blocks), (b"abcdefghij".to_vec(), vec![]));
+
+        let mut broken = two.clone();
+        broken[2] = 0x07;
+        let input = [one.clone(), broken, last.clone()].concat();
+        let (data, problems) = inflate_blocks(&input, &blocks);
+        assert_eq!(data, b"abcd\0\0\0\0ij");
+        assert_eq!(problems, vec![format!("zlib block 1 {:#x}..{:#x} didn't inflate", one.len(), one.len() + two.len())]);
+
+        let (data, problems) = inflate_blocks(&[one.clone(), two.clone()].concat(), &blocks);
+        assert_eq!(data, b"abcdefgh\0\0\0\0");
+        assert_eq!(problems.len(), 2);
+    }
+
+    #[test]
+    fn bad_first_block_stops_it() {
+        let (one, two) = (stored(b"abcd"), stored(b"efgh"));
+        let blocks = [one.len() as u16 - 1, two.len() as u16 - 1];
+        let mut broken = one.clone();
+        broken[2] = 0x07;
+        let (data, problems) = inflate_blocks(&[broken, two].concat(), &blocks);
+        assert_eq!(data, b"");
+        assert_eq!(problems, vec![format!("zlib block 0 0x0..{:#x} didn't inflate, so the block size isn't known", one.len())]);
+    }
+}
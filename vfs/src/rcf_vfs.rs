@@ -0,0 +1,19 @@
+use std::borrow::Cow;
+use std::io;
+
+use rcf::RcfArchive;
+
+use crate::{bad, not_found, Vfs};
+
+impl Vfs for RcfArchive {
+    fn list(&self) -> Vec<String> {
+        self.entries.iter().map(|e| e.name.clone()).collect()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Cow<'_, [u8]>> {
+        let entry = self.find(path).ok_or_else(|| not_found(path))?;
+        RcfArchive::read(self, entry)
+            .map(Cow::Borrowed)
+            .ok_or_else(|| bad(&format!("{} {:#x}+{:#x} is past the end of the archive", path, entry.offset, entry.size)))
+    }
+}